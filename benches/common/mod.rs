@@ -0,0 +1,38 @@
+//! Helpers shared by the benchmarks of every day. Each benchmark is its own
+//! crate, and only uses some of them.
+#![allow(dead_code)]
+
+use criterion::{black_box, measurement::WallTime, BenchmarkGroup, BenchmarkId};
+use rand::{rngs::StdRng, SeedableRng};
+use std::fmt;
+
+/// rng returns the random number generator the inputs of the given day are
+/// generated with. Its seed is fixed, so that runs are comparable.
+pub fn rng(day: u64) -> StdRng {
+    StdRng::seed_from_u64(day)
+}
+
+/// phases times the parsing of `input` and each part separately, recording
+/// them under the given size in the group.
+pub fn phases<T, E, A, B>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    size: usize,
+    input: &str,
+    parse: impl Fn(&str) -> Result<T, E>,
+    part1: impl Fn(&T) -> A,
+    part2: impl Fn(&T) -> B,
+) where
+    E: fmt::Debug,
+{
+    let parsed = parse(input).unwrap();
+
+    group.bench_with_input(BenchmarkId::new("parse", size), input, |b, input| {
+        b.iter(|| parse(black_box(input)).unwrap())
+    });
+    group.bench_with_input(BenchmarkId::new("part1", size), &parsed, |b, parsed| {
+        b.iter(|| part1(black_box(parsed)))
+    });
+    group.bench_with_input(BenchmarkId::new("part2", size), &parsed, |b, parsed| {
+        b.iter(|| part2(black_box(parsed)))
+    });
+}
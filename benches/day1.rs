@@ -0,0 +1,77 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use itertools::Itertools;
+use rand::Rng;
+use twentytwentytwo::day1;
+
+mod common;
+
+/// elves generates the payloads of `count` elves, each carrying a handful of
+/// items.
+fn elves(count: usize) -> Vec<Vec<u64>> {
+    let mut rng = common::rng(1);
+
+    (0..count)
+        .map(|_| {
+            (0..rng.gen_range(1..8))
+                .map(|_| rng.gen_range(1_000..10_000))
+                .collect()
+        })
+        .collect()
+}
+
+/// inventory renders the payloads as the puzzle input, one item per line
+/// and elves separated by a blank line.
+fn inventory(elves: &[Vec<u64>]) -> String {
+    elves
+        .iter()
+        .map(|payload| payload.iter().join("\n"))
+        .join("\n\n")
+}
+
+fn phases(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day1");
+
+    for count in [1_000, 10_000, 100_000] {
+        let input = inventory(&elves(count));
+        common::phases(
+            &mut group,
+            count,
+            &input,
+            day1::parse,
+            day1::part1,
+            day1::part2,
+        );
+    }
+
+    group.finish();
+}
+
+fn top_k(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day1/top_k");
+    group.sample_size(10);
+
+    for count in [1_000_000, 4_000_000] {
+        let input = elves(count);
+
+        group.bench_with_input(BenchmarkId::new("sorted", count), &input, |b, input| {
+            b.iter(|| {
+                input
+                    .iter()
+                    .map(|payload| payload.iter().sum::<u64>())
+                    .sorted()
+                    .rev()
+                    .take(3)
+                    .sum::<u64>()
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("heap", count), &input, |b, input| {
+            b.iter(|| day1::solve_part2(black_box(input)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, phases, top_k);
+criterion_main!(benches);
@@ -0,0 +1,40 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use itertools::Itertools;
+use rand::Rng;
+use twentytwentytwo::day2;
+
+mod common;
+
+/// guide generates a strategy guide of `count` rounds.
+fn guide(count: usize) -> String {
+    let mut rng = common::rng(2);
+
+    (0..count)
+        .map(|_| {
+            let opponent = rng.gen_range(b'A'..=b'C') as char;
+            let response = rng.gen_range(b'X'..=b'Z') as char;
+            format!("{opponent} {response}")
+        })
+        .join("\n")
+}
+
+fn phases(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day2");
+
+    for count in [1_000, 10_000, 100_000] {
+        let input = guide(count);
+        common::phases(
+            &mut group,
+            count,
+            &input,
+            day2::parse,
+            day2::part1,
+            day2::part2,
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, phases);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::{seq::SliceRandom, Rng};
+use twentytwentytwo::day3;
+
+mod common;
+
+const ITEMS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// supplies generates `groups` groups of three rucksacks, each holding
+/// `2 * size` items.
+///
+/// As in the puzzle, a single item type is found in both compartments of a
+/// rucksack, and a single one, the badge, in all three rucksacks of a group.
+fn supplies(groups: usize, size: usize) -> String {
+    let mut rng = common::rng(3);
+    let mut rucksacks = Vec::with_capacity(groups * 3);
+
+    for _ in 0..groups {
+        let mut items = ITEMS.to_vec();
+        items.shuffle(&mut rng);
+        let (badge, items) = items.split_first().unwrap();
+
+        // Each rucksack of the group draws from its own 17 item types, split
+        // between both compartments but for the one they share.
+        for pool in items.chunks(17) {
+            let (shared, pool) = pool.split_first().unwrap();
+            let (left, right) = pool.split_at(pool.len() / 2);
+
+            let mut first = vec![*shared, *badge];
+            first.extend((2..size).map(|_| *left.choose(&mut rng).unwrap()));
+            first.shuffle(&mut rng);
+
+            let mut second = vec![*shared];
+            second.extend((1..size).map(|_| *right.choose(&mut rng).unwrap()));
+            second.shuffle(&mut rng);
+
+            if rng.gen() {
+                std::mem::swap(&mut first, &mut second);
+            }
+            first.extend(second);
+            rucksacks.push(String::from_utf8(first).unwrap());
+        }
+    }
+
+    rucksacks.join("\n")
+}
+
+fn phases(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day3");
+
+    for groups in [1_000, 10_000, 100_000] {
+        let input = supplies(groups, 12);
+        common::phases(
+            &mut group,
+            groups,
+            &input,
+            day3::parse,
+            day3::part1,
+            day3::part2,
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, phases);
+criterion_main!(benches);
@@ -0,0 +1,41 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use itertools::Itertools;
+use rand::Rng;
+use twentytwentytwo::day4;
+
+mod common;
+
+/// assignments generates `count` pairs of section assignments.
+fn assignments(count: usize) -> String {
+    let mut rng = common::rng(4);
+    let mut range = || {
+        let start = rng.gen_range(1..100);
+        let end = rng.gen_range(start..100);
+        format!("{start}-{end}")
+    };
+
+    (0..count)
+        .map(|_| format!("{},{}", range(), range()))
+        .join("\n")
+}
+
+fn phases(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day4");
+
+    for count in [1_000, 10_000, 100_000] {
+        let input = assignments(count);
+        common::phases(
+            &mut group,
+            count,
+            &input,
+            day4::parse,
+            day4::part1,
+            day4::part2,
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, phases);
+criterion_main!(benches);
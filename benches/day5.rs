@@ -0,0 +1,62 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use itertools::Itertools;
+use rand::{seq::SliceRandom, Rng};
+use twentytwentytwo::day5;
+
+mod common;
+
+const STACKS: usize = 9;
+
+/// procedure generates a drawing of nine stacks of `height` crates, followed
+/// by `count` instructions. Instructions never move more crates than their
+/// stack holds.
+fn procedure(height: usize, count: usize) -> String {
+    let mut rng = common::rng(5);
+    let mut heights = [height; STACKS];
+
+    let mut lines = (0..height)
+        .map(|_| {
+            (0..STACKS)
+                .map(|_| format!("[{}]", rng.gen_range(b'A'..=b'Z') as char))
+                .join(" ")
+        })
+        .collect::<Vec<_>>();
+    lines.push((1..=STACKS).map(|stack| format!(" {stack} ")).join(" "));
+    lines.push(String::new());
+
+    for _ in 0..count {
+        let loaded = (0..STACKS)
+            .filter(|&stack| heights[stack] > 0)
+            .collect::<Vec<_>>();
+        let from = *loaded.choose(&mut rng).unwrap();
+        let to = (from + rng.gen_range(1..STACKS)) % STACKS;
+        let quantity = rng.gen_range(1..=heights[from].min(10));
+
+        heights[from] -= quantity;
+        heights[to] += quantity;
+        lines.push(format!("move {quantity} from {} to {}", from + 1, to + 1));
+    }
+
+    lines.join("\n")
+}
+
+fn phases(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day5");
+
+    for count in [1_000, 10_000, 100_000] {
+        let input = procedure(50, count);
+        common::phases(
+            &mut group,
+            count,
+            &input,
+            day5::parse,
+            |procedure| day5::part1(procedure).unwrap(),
+            |procedure| day5::part2(procedure).unwrap(),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, phases);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use twentytwentytwo::day6::{self, Datastream};
+
+mod common;
+
+/// datastream generates a signal of `length` letters whose first marker of
+/// `size` distinct letters is at its very end, so that solving it scans the
+/// whole signal.
+fn datastream(size: usize, length: usize) -> Datastream {
+    let mut rng = common::rng(6);
+
+    Datastream {
+        signal: day6::generate_signal(&mut rng, size, length, length),
+    }
+}
+
+/// phases times the parsing of the signal and each part separately, over
+/// growing signal lengths. Each part gets its own signal, with its marker at
+/// the end, so the parts can't share a parsed input as in the other days.
+fn phases(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day6");
+
+    for length in [10_000, 100_000, 1_000_000] {
+        let packet = datastream(4, length);
+        let message = datastream(14, length);
+        let input = String::from_utf8(message.signal.clone()).unwrap();
+        assert_eq!(day6::part1(&packet), Some(length as u64));
+        assert_eq!(day6::part2(&message), Some(length as u64));
+
+        group.bench_with_input(BenchmarkId::new("parse", length), &input, |b, input| {
+            b.iter(|| day6::parse(black_box(input)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("part1", length), &packet, |b, packet| {
+            b.iter(|| day6::part1(black_box(packet)))
+        });
+        group.bench_with_input(BenchmarkId::new("part2", length), &message, |b, message| {
+            b.iter(|| day6::part2(black_box(message)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, phases);
+criterion_main!(benches);
@@ -0,0 +1,92 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::Rng;
+use twentytwentytwo::day7;
+
+mod common;
+
+/// Shape is how the directories of a generated filesystem are nested.
+#[derive(Clone, Copy, Debug)]
+enum Shape {
+    /// Each directory is put in a random earlier one, which keeps the tree
+    /// shallow and bushy.
+    Bushy,
+
+    /// Each directory is put in the previous one, which makes the tree a
+    /// single path as deep as there are directories.
+    Deep,
+}
+
+impl Shape {
+    fn name(self) -> &'static str {
+        match self {
+            Shape::Bushy => "bushy",
+            Shape::Deep => "deep",
+        }
+    }
+}
+
+/// terminal generates the terminal output exploring a filesystem of `dirs`
+/// directories besides the root, each holding a few files.
+fn terminal(dirs: usize, shape: Shape) -> String {
+    let mut rng = common::rng(7);
+
+    let mut children = vec![Vec::new(); dirs + 1];
+    for dir in 1..=dirs {
+        let parent = match shape {
+            Shape::Bushy => rng.gen_range(0..dir),
+            Shape::Deep => dir - 1,
+        };
+        children[parent].push(dir);
+    }
+
+    // The directories are listed depth-first, as they would be explored by
+    // hand, with an explicit stack so that deep trees can be generated.
+    let mut lines = vec!["$ cd /".to_string()];
+    let mut stack = vec![(0, 0)];
+    while let Some((dir, next)) = stack.pop() {
+        if next == 0 {
+            lines.push("$ ls".to_string());
+            lines.extend(children[dir].iter().map(|child| format!("dir d{child}")));
+            lines.extend(
+                (0..rng.gen_range(1..4))
+                    .map(|file| format!("{} f{file}.txt", rng.gen_range(1..200_000))),
+            );
+        }
+
+        if let Some(&child) = children[dir].get(next) {
+            lines.push(format!("$ cd d{child}"));
+            stack.push((dir, next + 1));
+            stack.push((child, 0));
+        } else if !stack.is_empty() {
+            lines.push("$ cd ..".to_string());
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// phases times the parsing of the terminal output, which rebuilds the
+/// directory tree, and each part, which size every directory, separately.
+/// Both shapes of tree are measured over growing numbers of directories.
+fn phases(c: &mut Criterion) {
+    for shape in [Shape::Bushy, Shape::Deep] {
+        let mut group = c.benchmark_group(format!("day7/{}", shape.name()));
+
+        for dirs in [100, 1_000, 10_000] {
+            let input = terminal(dirs, shape);
+            common::phases(
+                &mut group,
+                dirs,
+                &input,
+                day7::parse,
+                day7::part1,
+                day7::part2,
+            );
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, phases);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use itertools::Itertools;
+use rand::Rng;
+use twentytwentytwo::day8;
+
+mod common;
+
+/// forest generates the height map of a square forest of `size` rows of
+/// `size` trees.
+fn forest(size: usize) -> String {
+    let mut rng = common::rng(8);
+
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+/// phases times the parsing of the height map and each part separately, over
+/// growing sizes of forest. Each part scans the rows and columns of every
+/// tree, so solving grows faster than the number of trees.
+fn phases(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day8");
+    group.sample_size(10);
+
+    for size in [50, 100, 200, 400] {
+        let input = forest(size);
+        common::phases(
+            &mut group,
+            size,
+            &input,
+            day8::parse,
+            day8::part1,
+            day8::part2,
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, phases);
+criterion_main!(benches);
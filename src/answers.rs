@@ -0,0 +1,173 @@
+use std::{collections::BTreeMap, fmt};
+
+/// AnswerSheet holds the expected answers to the puzzles of a year, keyed by
+/// day and part.
+///
+/// It is stored as a TOML document holding a table per day, named `dayN`,
+/// itself holding the answer to each part, named `partN`:
+///
+/// ```toml
+/// [day1]
+/// part1 = "24000"
+/// part2 = "45000"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AnswerSheet {
+    answers: BTreeMap<(u8, u8), String>,
+}
+
+impl AnswerSheet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// parse parses an answer sheet. Answers may be written either as
+    /// strings or as integers.
+    pub fn parse(input: &str) -> Result<Self, AnswersError> {
+        let document: toml::value::Table =
+            toml::from_str(input).map_err(|err| AnswersError::Syntax(err.to_string()))?;
+
+        let mut sheet = AnswerSheet::new();
+        for (day_key, parts) in document {
+            let day = numbered(&day_key, "day")
+                .ok_or_else(|| AnswersError::InvalidKey(day_key.clone()))?;
+            let parts = parts
+                .as_table()
+                .ok_or_else(|| AnswersError::InvalidKey(day_key.clone()))?;
+
+            for (part_key, answer) in parts {
+                let key = format!("{day_key}.{part_key}");
+                let part = numbered(part_key, "part")
+                    .filter(|part| (1..=2).contains(part))
+                    .ok_or_else(|| AnswersError::InvalidKey(key.clone()))?;
+                let answer = match answer {
+                    toml::Value::String(answer) => answer.clone(),
+                    toml::Value::Integer(answer) => answer.to_string(),
+                    _ => return Err(AnswersError::InvalidAnswer(key)),
+                };
+
+                sheet.set(day, part, answer);
+            }
+        }
+
+        Ok(sheet)
+    }
+
+    /// get returns the expected answer to the given part of the puzzle of the
+    /// given day, if any.
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.answers.get(&(day, part)).map(String::as_str)
+    }
+
+    /// set records the expected answer to the given part of the puzzle of
+    /// the given day, and returns the one it replaces, if any.
+    pub fn set(&mut self, day: u8, part: u8, answer: impl Into<String>) -> Option<String> {
+        self.answers.insert((day, part), answer.into())
+    }
+}
+
+/// numbered returns the number following `prefix` in `key`, such as 3 for
+/// `day3`.
+fn numbered(key: &str, prefix: &str) -> Option<u8> {
+    key.strip_prefix(prefix)?.parse().ok().filter(|&n| n > 0)
+}
+
+/// AnswerSheet is displayed as the TOML document it is stored as, days and
+/// parts in ascending order.
+impl fmt::Display for AnswerSheet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut previous = None;
+
+        for (&(day, part), answer) in &self.answers {
+            if previous != Some(day) {
+                if previous.is_some() {
+                    writeln!(f)?;
+                }
+                writeln!(f, "[day{day}]")?;
+                previous = Some(day);
+            }
+
+            writeln!(f, "part{part} = {}", toml::Value::String(answer.clone()))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// AnswersError describes why an answer sheet could not be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnswersError {
+    /// The sheet is not a valid TOML document.
+    Syntax(String),
+
+    /// A table is not named `dayN`, or a key is not named `part1` or `part2`.
+    InvalidKey(String),
+
+    /// An answer is neither a string nor an integer.
+    InvalidAnswer(String),
+}
+
+impl fmt::Display for AnswersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnswersError::Syntax(err) => write!(f, "invalid answer sheet: {err}"),
+            AnswersError::InvalidKey(key) => write!(f, "unexpected key {key:?}"),
+            AnswersError::InvalidAnswer(key) => {
+                write!(f, "{key} should be a string or an integer")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AnswersError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut sheet = AnswerSheet::new();
+        sheet.set(10, 1, "13140");
+        sheet.set(1, 2, "45000");
+        sheet.set(1, 1, "24000");
+        sheet.set(5, 1, "C\"MZ");
+
+        let document = sheet.to_string();
+        assert_eq!(
+            document,
+            "[day1]\npart1 = \"24000\"\npart2 = \"45000\"\n\n[day5]\npart1 = \"C\\\"MZ\"\n\n[day10]\npart1 = \"13140\"\n"
+        );
+        assert_eq!(AnswerSheet::parse(&document), Ok(sheet));
+    }
+
+    #[test]
+    fn test_parse() {
+        let sheet = AnswerSheet::parse("[day2]\npart1 = 15\npart2 = \"12\"\n").unwrap();
+
+        assert_eq!(sheet.get(2, 1), Some("15"));
+        assert_eq!(sheet.get(2, 2), Some("12"));
+        assert_eq!(sheet.get(1, 1), None);
+        assert_eq!(AnswerSheet::parse(""), Ok(AnswerSheet::new()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            AnswerSheet::parse("[dec1]\npart1 = 1"),
+            Err(AnswersError::InvalidKey("dec1".to_string()))
+        );
+        assert_eq!(
+            AnswerSheet::parse("[day1]\npart3 = 1"),
+            Err(AnswersError::InvalidKey("day1.part3".to_string()))
+        );
+        assert_eq!(
+            AnswerSheet::parse("[day1]\npart1 = 1.5"),
+            Err(AnswersError::InvalidAnswer("day1.part1".to_string()))
+        );
+        assert!(matches!(
+            AnswerSheet::parse("[day1"),
+            Err(AnswersError::Syntax(_))
+        ));
+    }
+}
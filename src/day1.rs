@@ -1,17 +1,21 @@
 use itertools::Itertools;
-use nom::{
-    bytes::complete::tag,
-    character::complete::digit1,
-    combinator::map_res,
-    error::context,
-    multi::{many1, separated_list1},
-    sequence::terminated,
-    IResult,
+use serde::Serialize;
+use std::{
+    cmp::{self, Reverse},
+    collections::{BinaryHeap, HashMap},
+    fmt,
+    io::{self, BufRead},
+    mem,
+};
+
+use crate::{
+    error::ParseError,
+    solution::{self, Solution},
 };
 
 #[aoc_generator(day1)]
-pub fn input_generator(input: &str) -> Vec<Vec<u64>> {
-    parse_payloads(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<Vec<Vec<u64>>, CaloriesError> {
+    parse_payloads(input)
 }
 
 #[aoc(day1, part1)]
@@ -24,26 +28,898 @@ pub fn solve_part1(input: &[Vec<u64>]) -> u64 {
 
 #[aoc(day1, part2)]
 pub fn solve_part2(input: &[Vec<u64>]) -> u64 {
-    input
+    heaviest(input, 3).iter().map(|load| load.calories).sum()
+}
+
+/// Inventory lists the calories of the items carried by each elf.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Inventory {
+    /// The calories of each item, grouped by elf, in input order.
+    pub elves: Vec<Vec<u64>>,
+}
+
+/// parse parses the calories list, one item per line, the elves being
+/// separated by blank lines.
+pub fn parse(input: &str) -> Result<Inventory, ParseError> {
+    Ok(Inventory {
+        elves: parse_payloads(input)?,
+    })
+}
+
+/// part1 returns the calories carried by the richest elf.
+pub fn part1(inventory: &Inventory) -> u64 {
+    solve_part1(&inventory.elves)
+}
+
+/// part2 returns the calories carried by the three richest elves.
+pub fn part2(inventory: &Inventory) -> u64 {
+    solve_part2(&inventory.elves)
+}
+
+/// Day1 is the [`Solution`] to the calorie counting puzzle.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Day1;
+
+impl Solution for Day1 {
+    type Input = Inventory;
+    type Output = u64;
+
+    fn parse(&self, input: &str) -> Result<Inventory, solution::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Inventory) -> Result<u64, solution::Error> {
+        Ok(part1(input))
+    }
+
+    fn part2(&self, input: &Inventory) -> Result<u64, solution::Error> {
+        Ok(part2(input))
+    }
+}
+
+/// stream_part1 solves part 1 from the calories list read from `reader`,
+/// holding a single line in memory at a time.
+pub fn stream_part1<R: BufRead>(reader: R) -> io::Result<u64> {
+    totals(reader).fold_ok(0, cmp::max)
+}
+
+/// stream_part2 solves part 2 from the calories list read from `reader`,
+/// holding a single line and the three richest elves in memory at a time.
+pub fn stream_part2<R: BufRead>(reader: R) -> io::Result<u64> {
+    itertools::process_results(totals(reader), |totals| {
+        top_k(totals, 3)
+            .into_iter()
+            .map(|(_, calories)| calories)
+            .sum()
+    })
+}
+
+/// totals returns an iterator over the calories carried by each elf, read
+/// from `reader` one line at a time. Blank lines are tolerated as in
+/// [`input_generator`], and invalid lines are reported as
+/// [`io::ErrorKind::InvalidData`] errors wrapping a [`CaloriesError`].
+pub fn totals<R: BufRead>(reader: R) -> Totals<R> {
+    Totals {
+        lines: reader.lines(),
+        line: 0,
+        calories: 0,
+    }
+}
+
+/// Totals is the iterator returned by [`totals`].
+#[derive(Debug)]
+pub struct Totals<R> {
+    lines: io::Lines<R>,
+    line: usize,
+
+    /// The calories carried by all the elves read so far, which must fit a
+    /// `u64` as in [`input_generator`].
+    calories: u64,
+}
+
+impl<R: BufRead> Iterator for Totals<R> {
+    type Item = io::Result<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut total = None;
+
+        for line in self.lines.by_ref() {
+            self.line += 1;
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err)),
+            };
+
+            let line = line.trim();
+            if line.is_empty() {
+                if total.is_some() {
+                    break;
+                }
+                continue;
+            }
+
+            let calories = calories(self.line, line).and_then(|calories| {
+                self.calories = self
+                    .calories
+                    .checked_add(calories)
+                    .ok_or(CaloriesError::Overflow { line: self.line })?;
+                Ok(calories)
+            });
+            match calories {
+                Ok(calories) => *total.get_or_insert(0) += calories,
+                Err(err) => return Some(Err(io::Error::new(io::ErrorKind::InvalidData, err))),
+            }
+        }
+
+        total.map(Ok)
+    }
+}
+
+/// heaviest returns the `k` elves carrying the most calories, from the
+/// richest one.
+pub fn heaviest(input: &[Vec<u64>], k: usize) -> Vec<Load> {
+    top_k(input.iter().map(|payload| payload.iter().sum::<u64>()), k)
+        .into_iter()
+        .map(|(elf, calories)| Load { elf, calories })
+        .collect()
+}
+
+/// top_k returns the `k` largest values along with their index, from the
+/// largest one. Equal values are ranked by index.
+///
+/// The values seen so far are kept in a min-heap bounded to `k` entries, so
+/// that each value costs O(log k) at most, and the values don't need to be
+/// collected first.
+pub fn top_k<T: Ord>(values: impl IntoIterator<Item = T>, k: usize) -> Vec<(usize, T)> {
+    if k == 0 {
+        return Vec::new();
+    }
+
+    // The heap's minimum is the smallest value, and of those, the last one.
+    let mut heap: BinaryHeap<Reverse<(T, Reverse<usize>)>> = BinaryHeap::with_capacity(k + 1);
+    for (idx, value) in values.into_iter().enumerate() {
+        let entry = Reverse((value, Reverse(idx)));
+
+        if heap.len() < k {
+            heap.push(entry);
+        } else if let Some(mut min) = heap.peek_mut() {
+            if entry < *min {
+                *min = entry;
+            }
+        }
+    }
+
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((value, Reverse(idx)))| (idx, value))
+        .collect()
+}
+
+/// Statistics describes how calories are spread among the elves.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Statistics {
+    /// The number of elves.
+    pub elves: usize,
+
+    /// The elf carrying the most calories, the first one in case of a tie.
+    pub richest: Load,
+
+    /// The elves carrying the most calories, from the richest one.
+    pub top: Vec<Load>,
+
+    /// The median of the calories carried by each elf.
+    pub median: f64,
+
+    /// The mean of the calories carried by each elf.
+    pub mean: f64,
+
+    /// The population standard deviation of the calories carried by each elf.
+    pub std_dev: f64,
+
+    /// The calories carried by each elf.
+    pub totals: Vec<u64>,
+
+    /// The number of items carried by each elf.
+    pub item_counts: Vec<usize>,
+}
+
+/// Load is the total calories carried by a single elf.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Load {
+    pub elf: usize,
+    pub calories: u64,
+}
+
+/// statistics computes the calories statistics of the elves, reporting the
+/// `k` richest ones. It returns `None` when there are no elves.
+pub fn statistics(input: &[Vec<u64>], k: usize) -> Option<Statistics> {
+    if input.is_empty() {
+        return None;
+    }
+
+    let totals = input
         .iter()
         .map(|payload| payload.iter().sum::<u64>())
-        .sorted()
-        .rev()
-        .take(3)
-        .sum()
+        .collect_vec();
+
+    let mut top = heaviest(input, k.max(1));
+    let richest = top[0];
+    top.truncate(k);
+
+    let count = totals.len() as f64;
+    let mean = totals.iter().map(|&total| total as f64).sum::<f64>() / count;
+    let variance = totals
+        .iter()
+        .map(|&total| (total as f64 - mean).powi(2))
+        .sum::<f64>()
+        / count;
+
+    let mut sorted = totals.clone();
+    let middle = sorted.len() / 2;
+    let (lower, &mut upper, _) = sorted.select_nth_unstable(middle);
+    let median = if totals.len() % 2 == 0 {
+        (*lower.iter().max().unwrap() as f64 + upper as f64) / 2.0
+    } else {
+        upper as f64
+    };
+
+    Some(Statistics {
+        elves: input.len(),
+        richest,
+        top,
+        median,
+        mean,
+        std_dev: variance.sqrt(),
+        totals,
+        item_counts: input.iter().map(Vec::len).collect(),
+    })
 }
 
-fn parse_payloads(input: &str) -> IResult<&str, Vec<Vec<u64>>> {
-    separated_list1(tag("\n"), payload)(input)
+impl Statistics {
+    /// to_json renders the statistics as a JSON document.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// EXACT_ITEM_LIMIT is the largest number of items [`rebalance`] searches
+/// exhaustively; larger inputs fall back to the LPT heuristic.
+pub const EXACT_ITEM_LIMIT: usize = 20;
+
+/// Strategy selects how snack items are redistributed among the elves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Strategy {
+    /// Branch and bound over every assignment of the items. The plan is
+    /// optimal, but the search is exponential in the number of items.
+    Exact,
+
+    /// Longest processing time first: the items, from the heaviest, each go
+    /// to the least loaded elf. The plan is within 4/3 - 1/(3m) of the
+    /// optimum for m elves.
+    Lpt,
+}
+
+/// Plan describes how to redistribute the snack items so that the most
+/// loaded elf carries as few calories as possible.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Plan {
+    /// The strategy the plan was computed with.
+    pub strategy: Strategy,
+
+    /// The calories carried by each elf once the plan is carried out.
+    pub payloads: Vec<Vec<u64>>,
+
+    /// The calories carried by the most loaded elf once the plan is carried
+    /// out.
+    pub max_load: u64,
+
+    /// A lower bound of the optimal maximum load: no elf can carry less than
+    /// the heaviest item, nor less than an even share of all the calories.
+    pub lower_bound: u64,
+
+    /// The maximum load is at most `bound` times the optimal one. It is 1.0
+    /// when the plan is known to be optimal.
+    pub bound: f64,
+
+    /// The items changing hands, ordered by giver and item.
+    pub transfers: Vec<Transfer>,
 }
 
-fn payload(input: &str) -> IResult<&str, Vec<u64>> {
-    context("payload", many1(calories))(input)
+/// Transfer is a single item handed over from an elf to another one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Transfer {
+    /// The elf giving the item away.
+    pub from: usize,
+
+    /// The elf receiving the item.
+    pub to: usize,
+
+    /// The index of the item in the giver's payload.
+    pub item: usize,
+
+    /// The calories of the item.
+    pub calories: u64,
 }
 
-fn calories(input: &str) -> IResult<&str, u64> {
-    context(
-        "calories",
-        terminated(map_res(digit1, |s: &str| s.parse::<u64>()), tag("\n")),
-    )(input)
+/// rebalance redistributes the snack items among the elves, searching for
+/// the optimal plan when there are at most [`EXACT_ITEM_LIMIT`] items, and
+/// using the LPT heuristic otherwise.
+pub fn rebalance(input: &[Vec<u64>]) -> Plan {
+    let items = input.iter().map(Vec::len).sum::<usize>();
+    let strategy = if items <= EXACT_ITEM_LIMIT {
+        Strategy::Exact
+    } else {
+        Strategy::Lpt
+    };
+
+    rebalance_with(input, strategy)
+}
+
+/// rebalance_with redistributes the snack items among the elves with the
+/// given strategy.
+///
+/// Items never get split: this is the multiprocessor scheduling problem,
+/// where the elves are the machines and the items the jobs. Once the items
+/// are grouped, the groups are handed to the elves already holding most of
+/// their items, so that few of them change hands.
+pub fn rebalance_with(input: &[Vec<u64>], strategy: Strategy) -> Plan {
+    let mut snacks = input
+        .iter()
+        .enumerate()
+        .flat_map(|(elf, payload)| {
+            payload
+                .iter()
+                .enumerate()
+                .map(move |(item, &calories)| (elf, item, calories))
+        })
+        .collect_vec();
+    snacks.sort_unstable_by_key(|&(elf, item, calories)| (Reverse(calories), elf, item));
+
+    let sizes = snacks
+        .iter()
+        .map(|&(_, _, calories)| calories)
+        .collect_vec();
+    let elves = input.len();
+    let lower_bound = match elves {
+        0 => 0,
+        _ => cmp::max(
+            sizes.first().copied().unwrap_or(0),
+            sizes.iter().sum::<u64>().div_ceil(elves as u64),
+        ),
+    };
+
+    let mut groups = lpt(&sizes, elves);
+    if strategy == Strategy::Exact {
+        groups = branch_and_bound(&sizes, elves, groups, lower_bound);
+    }
+
+    let max_load = load_of(&sizes, &groups, elves)
+        .into_iter()
+        .max()
+        .unwrap_or(0);
+    let bound = match strategy {
+        _ if max_load == lower_bound => 1.0,
+        Strategy::Exact => 1.0,
+        Strategy::Lpt => {
+            let graham = 4.0 / 3.0 - 1.0 / (3.0 * elves as f64);
+            graham.min(max_load as f64 / lower_bound as f64)
+        }
+    };
+
+    // Pair each group with the elf holding most of its items, the largest
+    // overlaps first; the groups left over go to the elves left over.
+    let mut overlaps: HashMap<(usize, usize), usize> = HashMap::new();
+    for (idx, &(elf, _, _)) in snacks.iter().enumerate() {
+        *overlaps.entry((groups[idx], elf)).or_default() += 1;
+    }
+    let mut overlaps = overlaps.into_iter().collect_vec();
+    overlaps.sort_unstable_by_key(|&((group, elf), count)| (Reverse(count), group, elf));
+
+    let mut owners: Vec<Option<usize>> = vec![None; elves];
+    let mut assigned = vec![false; elves];
+    for ((group, elf), _) in overlaps {
+        if owners[group].is_none() && !assigned[elf] {
+            owners[group] = Some(elf);
+            assigned[elf] = true;
+        }
+    }
+    let mut idle = (0..elves).filter(|&elf| !assigned[elf]);
+    let owners = owners
+        .into_iter()
+        .map(|owner| owner.or_else(|| idle.next()).unwrap())
+        .collect_vec();
+
+    let mut received = vec![Vec::new(); elves];
+    let mut transfers = Vec::new();
+    for (idx, &(from, item, calories)) in snacks.iter().enumerate() {
+        let to = owners[groups[idx]];
+        received[to].push((from, item, calories));
+        if from != to {
+            transfers.push(Transfer {
+                from,
+                to,
+                item,
+                calories,
+            });
+        }
+    }
+    transfers.sort_unstable_by_key(|transfer| (transfer.from, transfer.item));
+
+    let payloads = received
+        .into_iter()
+        .map(|mut items| {
+            items.sort_unstable_by_key(|&(from, item, _)| (from, item));
+            items.into_iter().map(|(_, _, calories)| calories).collect()
+        })
+        .collect();
+
+    Plan {
+        strategy,
+        payloads,
+        max_load,
+        lower_bound,
+        bound,
+        transfers,
+    }
+}
+
+/// lpt assigns each item, from the heaviest, to the least loaded of the
+/// `elves` groups, and returns the group of each item.
+fn lpt(sizes: &[u64], elves: usize) -> Vec<usize> {
+    let mut heap: BinaryHeap<Reverse<(u64, usize)>> =
+        (0..elves).map(|group| Reverse((0, group))).collect();
+
+    sizes
+        .iter()
+        .map(|&size| {
+            let mut least = heap.peek_mut().unwrap();
+            let Reverse((load, group)) = *least;
+            *least = Reverse((load + size, group));
+            group
+        })
+        .collect()
+}
+
+/// branch_and_bound searches for the assignment of the items, sorted from
+/// the heaviest, to `elves` groups minimising the maximum load. It starts
+/// from the `initial` assignment and stops early once it reaches the
+/// `lower_bound`.
+fn branch_and_bound(
+    sizes: &[u64],
+    elves: usize,
+    initial: Vec<usize>,
+    lower_bound: u64,
+) -> Vec<usize> {
+    struct Search<'a> {
+        sizes: &'a [u64],
+        lower_bound: u64,
+        loads: Vec<u64>,
+        current: Vec<usize>,
+        best: Vec<usize>,
+        best_max: u64,
+    }
+
+    impl Search<'_> {
+        fn branch(&mut self, idx: usize, max: u64) {
+            if self.best_max == self.lower_bound {
+                return;
+            }
+            if idx == self.sizes.len() {
+                self.best_max = max;
+                self.best.clone_from(&self.current);
+                return;
+            }
+
+            for group in 0..self.loads.len() {
+                // Groups with the same load are interchangeable: only the
+                // first of them needs to be explored.
+                if self.loads[..group].contains(&self.loads[group]) {
+                    continue;
+                }
+
+                let load = self.loads[group] + self.sizes[idx];
+                if load >= self.best_max {
+                    continue;
+                }
+
+                self.loads[group] = load;
+                self.current[idx] = group;
+                self.branch(idx + 1, cmp::max(max, load));
+                self.loads[group] -= self.sizes[idx];
+            }
+        }
+    }
+
+    let mut search = Search {
+        sizes,
+        lower_bound,
+        loads: vec![0; elves],
+        current: vec![0; sizes.len()],
+        best_max: load_of(sizes, &initial, elves)
+            .into_iter()
+            .max()
+            .unwrap_or(0),
+        best: initial,
+    };
+    search.branch(0, 0);
+
+    search.best
+}
+
+fn load_of(sizes: &[u64], groups: &[usize], elves: usize) -> Vec<u64> {
+    let mut loads = vec![0; elves];
+    for (&size, &group) in sizes.iter().zip(groups) {
+        loads[group] += size;
+    }
+    loads
+}
+
+/// CaloriesError describes an invalid line of the calories list. Lines are
+/// numbered from 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CaloriesError {
+    /// The line holds something else than a number.
+    InvalidNumber { line: usize, value: String },
+
+    /// The line holds a number too large to be represented, or one bringing
+    /// the calories carried by the elves over what can be represented.
+    Overflow { line: usize },
+}
+
+impl fmt::Display for CaloriesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        ParseError::from(self.clone()).fmt(f)
+    }
+}
+
+impl std::error::Error for CaloriesError {}
+
+impl From<CaloriesError> for ParseError {
+    fn from(err: CaloriesError) -> Self {
+        match err {
+            CaloriesError::InvalidNumber { line, value } => {
+                ParseError::new(line, format!("{value:?} is not a number of calories"))
+            }
+            CaloriesError::Overflow { line } => {
+                ParseError::new(line, format!("calories overflow {}", u64::MAX))
+            }
+        }
+    }
+}
+
+/// parse_payloads parses the calories carried by each elf, one item per
+/// line, the elves being separated by blank lines.
+///
+/// Both LF and CRLF line endings are accepted, and blank lines are
+/// tolerated anywhere: at the start or the end of the input, or several in
+/// a row between two elves.
+///
+/// The calories of all the elves must add up to a `u64`, so that summing
+/// any of them afterwards cannot overflow.
+fn parse_payloads(input: &str) -> Result<Vec<Vec<u64>>, CaloriesError> {
+    let mut payloads = Vec::new();
+    let mut payload = Vec::new();
+    let mut total = 0u64;
+
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            if !payload.is_empty() {
+                payloads.push(mem::take(&mut payload));
+            }
+            continue;
+        }
+
+        let calories = calories(idx + 1, line)?;
+        total = total
+            .checked_add(calories)
+            .ok_or(CaloriesError::Overflow { line: idx + 1 })?;
+        payload.push(calories);
+    }
+
+    if !payload.is_empty() {
+        payloads.push(payload);
+    }
+
+    Ok(payloads)
+}
+
+fn calories(line: usize, input: &str) -> Result<u64, CaloriesError> {
+    if !input.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(CaloriesError::InvalidNumber {
+            line,
+            value: input.to_string(),
+        });
+    }
+
+    input
+        .parse::<u64>()
+        .map_err(|_| CaloriesError::Overflow { line })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Vec<Vec<u64>> {
+        vec![
+            vec![1000, 2000, 3000],
+            vec![4000],
+            vec![5000, 6000],
+            vec![7000, 8000, 9000],
+            vec![10000],
+        ]
+    }
+
+    #[test]
+    fn test_parse_payloads() {
+        let expected = example();
+
+        for input in [
+            "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000",
+            "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n",
+            "1000\r\n2000\r\n3000\r\n\r\n4000\r\n\r\n5000\r\n6000\r\n\r\n7000\r\n8000\r\n9000\r\n\r\n10000\r\n",
+            "\n\n1000\n2000\n3000\n\n\n\n4000\n \n5000\n6000\n\n7000\n8000\n9000\n\n10000\n\n\n",
+        ] {
+            assert_eq!(input_generator(input), Ok(expected.clone()), "{input:?}");
+        }
+
+        assert_eq!(input_generator(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_parse_payloads_errors() {
+        assert_eq!(
+            input_generator("1\n\n2\n18446744073709551616\n"),
+            Err(CaloriesError::Overflow { line: 4 })
+        );
+        assert_eq!(
+            input_generator("18446744073709551615\n1\n"),
+            Err(CaloriesError::Overflow { line: 2 })
+        );
+        assert_eq!(
+            input_generator("18446744073709551615\n\n1\n"),
+            Err(CaloriesError::Overflow { line: 3 })
+        );
+        assert_eq!(
+            input_generator("1\n-2\n"),
+            Err(CaloriesError::InvalidNumber {
+                line: 2,
+                value: "-2".to_string()
+            })
+        );
+        assert_eq!(
+            CaloriesError::Overflow { line: 4 }.to_string(),
+            "line 4: calories overflow 18446744073709551615"
+        );
+    }
+
+    #[test]
+    fn test_parts() {
+        let inventory =
+            parse("1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000").unwrap();
+
+        assert_eq!(inventory.elves, example());
+        assert_eq!(part1(&inventory), 24000);
+        assert_eq!(part2(&inventory), 45000);
+        assert_eq!(
+            parse("1000\n\nabc\n").unwrap_err(),
+            ParseError::new(3, "\"abc\" is not a number of calories")
+        );
+    }
+
+    #[test]
+    fn test_stream() {
+        let input = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n";
+        let payloads = input_generator(input).unwrap();
+
+        assert_eq!(
+            stream_part1(input.as_bytes()).unwrap(),
+            solve_part1(&payloads)
+        );
+        assert_eq!(
+            stream_part2(input.as_bytes()).unwrap(),
+            solve_part2(&payloads)
+        );
+        assert_eq!(
+            totals("\r\n\n1\r\n2\r\n\r\n\r\n3".as_bytes())
+                .map(Result::unwrap)
+                .collect_vec(),
+            vec![3, 3]
+        );
+        assert_eq!(stream_part1(&b""[..]).unwrap(), 0);
+
+        let err = stream_part2("1\n\nx\n".as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "line 3: \"x\" is not a number of calories");
+
+        let err = stream_part1("18446744073709551615\n1\n".as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "line 2: calories overflow 18446744073709551615"
+        );
+    }
+
+    #[test]
+    fn test_statistics() {
+        let stats = statistics(&example(), 3).unwrap();
+
+        assert_eq!(stats.elves, 5);
+        assert_eq!(
+            stats.richest,
+            Load {
+                elf: 3,
+                calories: 24000
+            }
+        );
+        assert_eq!(
+            stats.top.iter().map(|load| load.elf).collect_vec(),
+            vec![3, 2, 4]
+        );
+        assert_eq!(
+            stats.top.iter().map(|load| load.calories).sum::<u64>(),
+            45000
+        );
+        assert_eq!(stats.median, 10000.0);
+        assert_eq!(stats.mean, 11000.0);
+        assert!((stats.std_dev - 48_800_000f64.sqrt()).abs() < 1e-9);
+        assert_eq!(stats.totals, vec![6000, 4000, 11000, 24000, 10000]);
+        assert_eq!(stats.item_counts, vec![3, 1, 2, 3, 1]);
+    }
+
+    #[test]
+    fn test_statistics_even_and_empty() {
+        let stats = statistics(&[vec![1], vec![4], vec![2, 1], vec![10]], 10).unwrap();
+        assert_eq!(stats.median, 3.5);
+        assert_eq!(stats.top.len(), 4);
+
+        assert_eq!(statistics(&[], 3), None);
+    }
+
+    #[test]
+    fn test_top_k() {
+        assert_eq!(
+            heaviest(&example(), 3),
+            vec![
+                Load {
+                    elf: 3,
+                    calories: 24000
+                },
+                Load {
+                    elf: 2,
+                    calories: 11000
+                },
+                Load {
+                    elf: 4,
+                    calories: 10000
+                },
+            ]
+        );
+        assert_eq!(solve_part2(&example()), 45000);
+
+        assert_eq!(top_k([3, 1, 3, 2, 3], 2), vec![(0, 3), (2, 3)]);
+        assert_eq!(top_k([1, 2], 5), vec![(1, 2), (0, 1)]);
+        assert_eq!(top_k([1, 2], 0), vec![]);
+        assert_eq!(top_k(Vec::<u64>::new(), 3), vec![]);
+    }
+
+    #[test]
+    fn test_statistics_json() {
+        let json = statistics(&example(), 1).unwrap().to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["richest"]["elf"], 3);
+        assert_eq!(value["top"][0]["calories"], 24000);
+        assert_eq!(value["median"], 10000.0);
+        assert_eq!(value["item_counts"][2], 2);
+    }
+
+    /// carry_out applies the plan's transfers to the input, and checks it
+    /// yields the plan's payloads.
+    fn carry_out(input: &[Vec<u64>], plan: &Plan) {
+        let mut payloads = input
+            .iter()
+            .enumerate()
+            .map(|(elf, payload)| {
+                payload
+                    .iter()
+                    .enumerate()
+                    .filter(|&(item, _)| {
+                        !plan
+                            .transfers
+                            .iter()
+                            .any(|transfer| transfer.from == elf && transfer.item == item)
+                    })
+                    .map(|(_, &calories)| calories)
+                    .collect_vec()
+            })
+            .collect_vec();
+        for transfer in &plan.transfers {
+            assert_eq!(input[transfer.from][transfer.item], transfer.calories);
+            payloads[transfer.to].push(transfer.calories);
+        }
+
+        for (payload, expected) in payloads.iter_mut().zip(&plan.payloads) {
+            let mut expected = expected.clone();
+            payload.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(*payload, expected);
+        }
+        assert_eq!(
+            plan.payloads.iter().map(|p| p.iter().sum::<u64>()).max(),
+            Some(plan.max_load)
+        );
+    }
+
+    #[test]
+    fn test_rebalance() {
+        let plan = rebalance(&example());
+
+        assert_eq!(plan.strategy, Strategy::Exact);
+        assert_eq!(plan.max_load, 11000);
+        assert_eq!(plan.lower_bound, 11000);
+        assert_eq!(plan.bound, 1.0);
+        assert!(plan
+            .payloads
+            .iter()
+            .all(|payload| payload.iter().sum::<u64>() == 11000));
+        carry_out(&example(), &plan);
+    }
+
+    #[test]
+    fn test_rebalance_lpt() {
+        let input = vec![vec![3, 3], vec![2, 2, 2]];
+
+        let exact = rebalance_with(&input, Strategy::Exact);
+        assert_eq!(exact.max_load, 6);
+        assert_eq!(exact.payloads, input);
+        assert!(exact.transfers.is_empty());
+
+        let lpt = rebalance_with(&input, Strategy::Lpt);
+        assert_eq!(lpt.max_load, 7);
+        assert_eq!(lpt.lower_bound, 6);
+        assert!((lpt.bound - 7.0 / 6.0).abs() < 1e-9);
+        assert_eq!(lpt.payloads, vec![vec![3, 2], vec![3, 2, 2]]);
+        assert_eq!(
+            lpt.transfers,
+            vec![
+                Transfer {
+                    from: 0,
+                    to: 1,
+                    item: 0,
+                    calories: 3
+                },
+                Transfer {
+                    from: 1,
+                    to: 0,
+                    item: 1,
+                    calories: 2
+                },
+            ]
+        );
+        carry_out(&input, &lpt);
+    }
+
+    #[test]
+    fn test_rebalance_large() {
+        let input = (1..=40u64)
+            .map(|elf| {
+                (0..elf % 7)
+                    .map(|item| elf * 37 % 101 + item * 13)
+                    .collect_vec()
+            })
+            .collect_vec();
+        let plan = rebalance(&input);
+
+        assert_eq!(plan.strategy, Strategy::Lpt);
+        assert!(plan.max_load >= plan.lower_bound);
+        assert!(plan.max_load as f64 <= plan.bound * plan.lower_bound as f64 + 1e-9);
+        assert!(plan.bound <= 4.0 / 3.0);
+        carry_out(&input, &plan);
+
+        let empty = rebalance(&[]);
+        assert_eq!(empty.max_load, 0);
+        assert!(empty.payloads.is_empty());
+    }
 }
@@ -2,12 +2,18 @@ use nom::{
     branch::alt,
     bytes::complete::tag,
     character::complete::multispace1,
-    combinator::{map, value},
+    combinator::{all_consuming, map, value},
     error::context,
     multi::separated_list1,
     sequence::separated_pair,
     IResult,
 };
+use std::io::{self, BufRead};
+
+use crate::{
+    error::ParseError,
+    solution::{self, Solution},
+};
 
 #[aoc_generator(day2, part1)]
 pub fn input_generator_part1(input: &str) -> Vec<Round> {
@@ -31,6 +37,113 @@ pub fn solve_part2(input: &[StrategizedRound]) -> i64 {
     input.iter().map(|round| round.score()).sum()
 }
 
+/// Guide is the encrypted strategy guide, read both ways: the second column
+/// being either our pick or the outcome to aim for.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Guide {
+    /// The rounds, the second column being our pick.
+    pub rounds: Vec<Round>,
+
+    /// The rounds, the second column being the outcome to aim for.
+    pub strategies: Vec<StrategizedRound>,
+}
+
+/// parse parses the strategy guide, one round per line. Blank lines are
+/// skipped.
+pub fn parse(input: &str) -> Result<Guide, ParseError> {
+    let mut guide = Guide::default();
+
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+
+        let invalid = |_| ParseError::new(idx + 1, format!("invalid round {line:?}"));
+        guide
+            .rounds
+            .push(all_consuming(round)(line).map_err(invalid)?.1);
+        guide
+            .strategies
+            .push(all_consuming(strategized_round)(line).map_err(invalid)?.1);
+    }
+
+    Ok(guide)
+}
+
+/// part1 returns the total score when the second column is our pick.
+pub fn part1(guide: &Guide) -> i64 {
+    solve_part1(&guide.rounds)
+}
+
+/// part2 returns the total score when the second column is the outcome to
+/// aim for.
+pub fn part2(guide: &Guide) -> i64 {
+    solve_part2(&guide.strategies)
+}
+
+/// Day2 is the [`Solution`] to the rock paper scissors puzzle.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Day2;
+
+impl Solution for Day2 {
+    type Input = Guide;
+    type Output = i64;
+
+    fn parse(&self, input: &str) -> Result<Guide, solution::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Guide) -> Result<i64, solution::Error> {
+        Ok(part1(input))
+    }
+
+    fn part2(&self, input: &Guide) -> Result<i64, solution::Error> {
+        Ok(part2(input))
+    }
+}
+
+/// stream_part1 solves part 1 from the strategy guide read from `reader`,
+/// one round at a time.
+pub fn stream_part1<R: BufRead>(reader: R) -> io::Result<i64> {
+    total_score(reader, round, Round::score)
+}
+
+/// stream_part2 solves part 2 from the strategy guide read from `reader`,
+/// one round at a time.
+pub fn stream_part2<R: BufRead>(reader: R) -> io::Result<i64> {
+    total_score(reader, strategized_round, StrategizedRound::score)
+}
+
+/// total_score parses each line read from `reader` with `parser`, and sums
+/// the scores of the rounds. Blank lines are skipped, and lines that fail to
+/// parse are reported as [`io::ErrorKind::InvalidData`] errors.
+fn total_score<R: BufRead, T>(
+    reader: R,
+    mut parser: impl FnMut(&str) -> IResult<&str, T>,
+    score: impl Fn(&T) -> i64,
+) -> io::Result<i64> {
+    let mut total = 0;
+
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (_, round) = all_consuming(&mut parser)(line).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: invalid round {line:?}", idx + 1),
+            )
+        })?;
+        total += score(&round);
+    }
+
+    Ok(total)
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Pick {
     Rock,
@@ -71,7 +184,7 @@ impl Pick {
 }
 
 /// Round of the game
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Round {
     pub them: Pick,
     pub us: Pick,
@@ -100,6 +213,7 @@ impl Round {
 
 /// StrategizedRound represents a round where we have a strategy for how to play
 /// against our opponent available to us.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct StrategizedRound {
     pub them: Pick,
     pub strategy: Strategy,
@@ -128,7 +242,7 @@ pub enum Outcome {
 
 /// Strategy represents the strategy to use against
 /// the opponent, as obtained from the elf's input
-type Strategy = Outcome;
+pub type Strategy = Outcome;
 
 fn strategized_round(input: &str) -> IResult<&str, StrategizedRound> {
     context(
@@ -423,6 +537,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parts() {
+        let guide = parse("A Y\nB X\nC Z").unwrap();
+
+        assert_eq!(guide.rounds.len(), 3);
+        assert_eq!(guide.strategies[1].strategy, Strategy::Lose);
+        assert_eq!(part1(&guide), 15);
+        assert_eq!(part2(&guide), 12);
+        assert_eq!(
+            parse("A Y\nB").unwrap_err(),
+            ParseError::new(2, "invalid round \"B\"")
+        );
+    }
+
+    #[test]
+    fn test_stream() {
+        let input = "A Y\nB X\nC Z\n";
+
+        assert_eq!(
+            stream_part1(input.as_bytes()).unwrap(),
+            solve_part1(&input_generator_part1(input))
+        );
+        assert_eq!(
+            stream_part2(input.as_bytes()).unwrap(),
+            solve_part2(&input_generator_part2(input))
+        );
+        assert_eq!(stream_part1(input.as_bytes()).unwrap(), 15);
+        assert_eq!(stream_part2("A Y\r\nB X\r\n\nC Z".as_bytes()).unwrap(), 12);
+
+        let err = stream_part1("A Y\nB W\n".as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "line 2: invalid round \"B W\"");
+    }
+
     #[test]
     fn test_round() {
         assert_eq!(
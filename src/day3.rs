@@ -1,4 +1,10 @@
 use itertools::Itertools;
+use std::io::{self, BufRead};
+
+use crate::{
+    error::ParseError,
+    solution::{self, Solution},
+};
 
 #[aoc_generator(day3, part1)]
 pub fn input_generator_part1(input: &str) -> Vec<Rucksack> {
@@ -39,10 +45,162 @@ pub fn solve_part2(input: &[Group]) -> u64 {
     input.iter().map(|g| g.priority()).sum()
 }
 
+/// Supplies lists the elves' rucksacks. The elves come in groups of three,
+/// in input order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Supplies {
+    pub rucksacks: Vec<Rucksack>,
+}
+
+/// parse parses the rucksacks, one per line, each holding item letters.
+/// Blank lines are skipped.
+pub fn parse(input: &str) -> Result<Supplies, ParseError> {
+    let mut rucksacks = Vec::new();
+    let mut last = 0;
+
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+        if !line.bytes().all(|b| b.is_ascii_alphabetic()) {
+            return Err(ParseError::new(
+                idx + 1,
+                format!("invalid rucksack {line:?}"),
+            ));
+        }
+
+        rucksacks.push(parse_rucksack(line));
+        last = idx + 1;
+    }
+
+    if rucksacks.len() % 3 != 0 {
+        return Err(ParseError::new(
+            last,
+            format!("incomplete group of {} rucksacks", rucksacks.len() % 3),
+        ));
+    }
+
+    Ok(Supplies { rucksacks })
+}
+
+/// part1 returns the sum of the priorities of the items found in both
+/// compartments of each rucksack.
+pub fn part1(supplies: &Supplies) -> u64 {
+    solve_part1(&supplies.rucksacks)
+}
+
+/// part2 returns the sum of the priorities of the badges, the item carried
+/// by all three elves of each group.
+pub fn part2(supplies: &Supplies) -> u64 {
+    supplies
+        .rucksacks
+        .chunks(3)
+        .map(|group| {
+            Group {
+                first: group[0].items(),
+                second: group[1].items(),
+                third: group[2].items(),
+            }
+            .priority()
+        })
+        .sum()
+}
+
+/// Day3 is the [`Solution`] to the rucksack reorganization puzzle.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input = Supplies;
+    type Output = u64;
+
+    fn parse(&self, input: &str) -> Result<Supplies, solution::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Supplies) -> Result<u64, solution::Error> {
+        Ok(part1(input))
+    }
+
+    fn part2(&self, input: &Supplies) -> Result<u64, solution::Error> {
+        Ok(part2(input))
+    }
+}
+
+/// stream_part1 solves part 1 from the rucksacks read from `reader`, one
+/// at a time.
+pub fn stream_part1<R: BufRead>(reader: R) -> io::Result<u64> {
+    let mut total = 0;
+
+    for line in items(reader) {
+        let (_, line) = line?;
+        total += parse_rucksack(&line).priority();
+    }
+
+    Ok(total)
+}
+
+/// stream_part2 solves part 2 from the rucksacks read from `reader`, one
+/// group of three at a time.
+pub fn stream_part2<R: BufRead>(reader: R) -> io::Result<u64> {
+    let mut total = 0;
+    let mut group = Vec::with_capacity(3);
+    let mut last = 0;
+
+    for line in items(reader) {
+        let (idx, line) = line?;
+        last = idx;
+        group.push(parse_compartment(&line));
+
+        if let Some((&first, &second, &third)) = group.iter().collect_tuple() {
+            total += Group {
+                first,
+                second,
+                third,
+            }
+            .priority();
+            group.clear();
+        }
+    }
+
+    if !group.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("line {last}: incomplete group of {} rucksacks", group.len()),
+        ));
+    }
+
+    Ok(total)
+}
+
+/// items returns an iterator over the non-blank lines read from `reader`,
+/// numbered from 1. Lines holding something else than item letters are
+/// reported as [`io::ErrorKind::InvalidData`] errors.
+fn items<R: BufRead>(reader: R) -> impl Iterator<Item = io::Result<(usize, String)>> {
+    reader
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            let line = line?;
+            let trimmed = line.trim_end();
+            if !trimmed.bytes().all(|b| b.is_ascii_alphabetic()) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: invalid rucksack {trimmed:?}", idx + 1),
+                ));
+            }
+            Ok((idx + 1, trimmed.to_string()))
+        })
+        .filter(|line| !matches!(line, Ok((_, line)) if line.is_empty()))
+}
+
+/// Group of three elves, each holding a set of items.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Group {
-    first: Compartment,
-    second: Compartment,
-    third: Compartment,
+    pub first: Compartment,
+    pub second: Compartment,
+    pub third: Compartment,
 }
 
 impl Group {
@@ -52,37 +210,32 @@ impl Group {
     }
 }
 
+/// Rucksack of an elf, its items split in two compartments.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Rucksack {
     pub first: Compartment,
     pub second: Compartment,
 }
 
 impl Rucksack {
+    /// items returns every item of the rucksack, in either compartment.
+    pub fn items(&self) -> Compartment {
+        self.first | self.second
+    }
+
     fn priority(&self) -> u64 {
         let difference: Compartment = self.first & self.second;
         priority_of(&difference)
     }
 }
 
-type Compartment = bitmaps::Bitmap<52>;
+/// Compartment is a set of items, item `i` having priority `i + 1`.
+pub type Compartment = bitmaps::Bitmap<52>;
 
 fn priority_of(s: &Compartment) -> u64 {
     s.into_iter().map(|idx| (idx + 1) as u64).sum::<u64>()
 }
 
-fn parse_group(input: &str) -> Group {
-    let mut lines = input.lines();
-    let first = parse_compartment(lines.next().unwrap());
-    let second = parse_compartment(lines.next().unwrap());
-    let third = parse_compartment(lines.next().unwrap());
-
-    Group {
-        first,
-        second,
-        third,
-    }
-}
-
 fn parse_rucksack(input: &str) -> Rucksack {
     let (lhs, rhs) = input.split_at(input.len() / 2);
     Rucksack {
@@ -95,10 +248,10 @@ fn parse_compartment(s: &str) -> Compartment {
     let mut compartment = Compartment::new();
 
     for c in s.chars() {
-        if ('a'..='z').contains(&c) {
+        if c.is_ascii_lowercase() {
             let bit_idx = (c as u32 - 'a' as u32) as usize;
             compartment.set(bit_idx, true);
-        } else if ('A'..='Z').contains(&c) {
+        } else if c.is_ascii_uppercase() {
             let bit_idx = (c as u32 - 'A' as u32 + 26) as usize;
             compartment.set(bit_idx, true);
         } else {
@@ -135,6 +288,61 @@ mod tests {
         assert_eq!(parse_rucksack("CrZsJsPPZsGzwwsLwLmpwMDw").priority(), 19);
     }
 
+    #[test]
+    fn test_parts() {
+        let input = [
+            "vJrwpWtwJgWrhcsFMMfFFhFp",
+            "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
+            "PmmdzqPrVvPwwTWBwg",
+            "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
+            "ttgJtRGJQctTZtZT",
+            "CrZsJsPPZsGzwwsLwLmpwMDw",
+        ]
+        .join("\n");
+        let supplies = parse(&input).unwrap();
+
+        assert_eq!(part1(&supplies), 157);
+        assert_eq!(part2(&supplies), 70);
+        assert_eq!(
+            parse("ab\ncd\n\nef\ngh").unwrap_err(),
+            ParseError::new(5, "incomplete group of 1 rucksacks")
+        );
+        assert_eq!(
+            parse("ab\nc1").unwrap_err(),
+            ParseError::new(2, "invalid rucksack \"c1\"")
+        );
+    }
+
+    #[test]
+    fn test_stream() {
+        let input = [
+            "vJrwpWtwJgWrhcsFMMfFFhFp",
+            "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
+            "PmmdzqPrVvPwwTWBwg",
+            "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
+            "ttgJtRGJQctTZtZT",
+            "CrZsJsPPZsGzwwsLwLmpwMDw",
+        ]
+        .join("\n");
+
+        assert_eq!(
+            stream_part1(input.as_bytes()).unwrap(),
+            solve_part1(&input_generator_part1(&input))
+        );
+        assert_eq!(
+            stream_part2(input.as_bytes()).unwrap(),
+            solve_part2(&input_generator_part2(&input))
+        );
+        assert_eq!(stream_part1(input.as_bytes()).unwrap(), 157);
+        assert_eq!(stream_part2(input.as_bytes()).unwrap(), 70);
+
+        let err = stream_part2("ab\r\ncd\r\n\r\nef\r\ngh\r\n".as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "line 5: incomplete group of 1 rucksacks");
+        let err = stream_part1("ab\na-b\n".as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "line 2: invalid rucksack \"a-b\"");
+    }
+
     #[test]
     fn test_parse_compartment() {
         let s = "azAZ";
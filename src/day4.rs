@@ -1,77 +1,515 @@
+use itertools::Itertools;
 use nom::{
+    branch::alt,
     bytes::complete::tag,
     character::complete::digit1,
-    combinator::{map, map_res},
+    combinator::{all_consuming, map, map_res, opt, recognize, verify},
     error::context,
-    sequence::separated_pair,
+    multi::separated_list1,
+    sequence::{pair, preceded, separated_pair, terminated},
     IResult,
 };
-use std::cmp;
+use std::{
+    cmp::{self, Reverse},
+    collections::BinaryHeap,
+    io::{self, BufRead},
+};
+
+use crate::{
+    error::ParseError,
+    solution::{self, Solution},
+};
 
 #[aoc_generator(day4)]
-pub fn input_generator_part1(input: &str) -> Vec<(Range, Range)> {
-    input
-        .lines()
-        .map(|line| range_pair(line).unwrap().1)
-        .collect()
+pub fn input_generator_part1(input: &str) -> Result<Vec<Vec<Range>>, ParseError> {
+    parse(input).map(|assignments| assignments.lines)
 }
 
 #[aoc(day4, part1)]
-pub fn solve_part1(input: &[(Range, Range)]) -> u64 {
+pub fn solve_part1(input: &[Vec<Range>]) -> u64 {
     input
         .iter()
-        .map(|(lhs, rhs)| {
-            u64::from(
-                ((lhs.min <= rhs.min) && (lhs.max >= rhs.max))
-                    || ((rhs.min <= lhs.min) && (rhs.max >= lhs.max)),
-            )
-        })
+        .map(|ranges| u64::from(contains_all(ranges)))
         .sum()
 }
 
 #[aoc(day4, part2)]
-pub fn solve_part2(input: &[(Range, Range)]) -> u64 {
+pub fn solve_part2(input: &[Vec<Range>]) -> u64 {
     input
         .iter()
-        .map(|(lhs, rhs)| match overlapps(lhs, rhs) {
+        .map(|ranges| match any_overlapp(ranges) {
             true => 1,
             false => 0,
         })
         .sum()
 }
 
+/// Assignments lists the section assignments of the elves, one group of
+/// elves per line.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Assignments {
+    /// The section range of each elf, grouped by line.
+    pub lines: Vec<Vec<Range>>,
+}
+
+/// parse parses the assignments, one comma-separated list of ranges per
+/// line. Blank lines are skipped.
+pub fn parse(input: &str) -> Result<Assignments, ParseError> {
+    let mut lines = Vec::new();
+
+    for (idx, line) in input.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (_, ranges) = all_consuming(range_list)(line)
+            .map_err(|_| ParseError::new(idx + 1, format!("invalid assignments {line:?}")))?;
+        lines.push(ranges);
+    }
+
+    Ok(Assignments { lines })
+}
+
+/// part1 returns the number of lines where one range contains all the others.
+pub fn part1(assignments: &Assignments) -> u64 {
+    solve_part1(&assignments.lines)
+}
+
+/// part2 returns the number of lines where any two ranges overlap.
+pub fn part2(assignments: &Assignments) -> u64 {
+    solve_part2(&assignments.lines)
+}
+
+/// Day4 is the [`Solution`] to the camp cleanup puzzle.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Day4;
+
+impl Solution for Day4 {
+    type Input = Assignments;
+    type Output = u64;
+
+    fn parse(&self, input: &str) -> Result<Assignments, solution::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Assignments) -> Result<u64, solution::Error> {
+        Ok(part1(input))
+    }
+
+    fn part2(&self, input: &Assignments) -> Result<u64, solution::Error> {
+        Ok(part2(input))
+    }
+}
+
+/// stream_part1 solves part 1 from the assignments read from `reader`, one
+/// line at a time.
+pub fn stream_part1<R: BufRead>(reader: R) -> io::Result<u64> {
+    count_lines(reader, contains_all)
+}
+
+/// stream_part2 solves part 2 from the assignments read from `reader`, one
+/// line at a time.
+pub fn stream_part2<R: BufRead>(reader: R) -> io::Result<u64> {
+    count_lines(reader, any_overlapp)
+}
+
+/// count_lines counts the lines of assignments read from `reader` matching
+/// `predicate`. Blank lines are skipped, and lines that fail to parse are
+/// reported as [`io::ErrorKind::InvalidData`] errors.
+fn count_lines<R: BufRead>(reader: R, predicate: impl Fn(&[Range]) -> bool) -> io::Result<u64> {
+    let mut count = 0;
+
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (_, ranges) = all_consuming(range_list)(line).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: invalid assignments {line:?}", idx + 1),
+            )
+        })?;
+        count += u64::from(predicate(&ranges));
+    }
+
+    Ok(count)
+}
+
+/// contains_all returns whether one of the ranges contains all the others.
+fn contains_all(ranges: &[Range]) -> bool {
+    ranges
+        .iter()
+        .any(|outer| ranges.iter().all(|inner| contains(outer, inner)))
+}
+
+/// any_overlapp returns whether any two of the ranges overlap.
+fn any_overlapp(ranges: &[Range]) -> bool {
+    ranges
+        .iter()
+        .tuple_combinations()
+        .any(|(lhs, rhs)| overlapps(lhs, rhs))
+}
+
+fn contains(lhs: &Range, rhs: &Range) -> bool {
+    (lhs.min <= rhs.min) && (lhs.max >= rhs.max)
+}
+
 fn overlapps(lhs: &Range, rhs: &Range) -> bool {
     cmp::max(lhs.min, rhs.min) <= cmp::min(lhs.max, rhs.max)
 }
 
-#[derive(Clone, Copy)]
+/// elves flattens the assignment lines into a single list of section ranges,
+/// one per elf, numbered in the order they appear in the input.
+pub fn elves(input: &[Vec<Range>]) -> Vec<Range> {
+    input.iter().flatten().copied().collect()
+}
+
+/// Coverage holds the result of analysing every elf's assignment against
+/// every other elf's, regardless of which line they were listed on.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Coverage {
+    /// Every pair of elf indices whose ranges share at least one section,
+    /// with the lowest index first, in ascending order.
+    pub overlapping_pairs: Vec<(usize, usize)>,
+
+    /// The highest number of elves assigned to any single section.
+    pub max_depth: usize,
+
+    /// The sections, between the lowest and highest assigned ones, that
+    /// no elf is assigned to.
+    pub uncovered: Vec<Range>,
+}
+
+/// coverage sweeps over the elves' ranges ordered by their lower bound,
+/// keeping the ranges still open at the current section in a min-heap keyed
+/// by their upper bound. Each range is pushed and popped once, so apart from
+/// reporting the overlapping pairs themselves, the sweep runs in O(n log n).
+pub fn coverage(elves: &[Range]) -> Coverage {
+    let mut order: Vec<usize> = (0..elves.len()).collect();
+    order.sort_unstable_by_key(|&idx| (elves[idx].min, elves[idx].max));
+
+    let mut result = Coverage::default();
+    let mut active: BinaryHeap<Reverse<(i64, usize)>> = BinaryHeap::new();
+    let mut reach: Option<i64> = None;
+
+    for idx in order {
+        let range = elves[idx];
+
+        while let Some(&Reverse((max, _))) = active.peek() {
+            if max >= range.min {
+                break;
+            }
+            active.pop();
+        }
+
+        // Every range still open ends at or after the current one starts,
+        // and started before it: they all overlap with it.
+        for &Reverse((_, other)) in active.iter() {
+            result
+                .overlapping_pairs
+                .push((cmp::min(idx, other), cmp::max(idx, other)));
+        }
+
+        active.push(Reverse((range.max, idx)));
+        result.max_depth = cmp::max(result.max_depth, active.len());
+
+        match reach {
+            Some(end) if end < i64::MAX && range.min > end + 1 => {
+                result.uncovered.push(Range {
+                    min: end + 1,
+                    max: range.min - 1,
+                });
+                reach = Some(range.max);
+            }
+            Some(end) => reach = Some(cmp::max(end, range.max)),
+            None => reach = Some(range.max),
+        }
+    }
+
+    result.overlapping_pairs.sort_unstable();
+    result
+}
+
+/// Schedule is the outcome of removing redundant assignments: the smallest
+/// set of elves that still cover exactly the same sections as all of them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Schedule {
+    /// Indices of the elves whose assignments are kept, in ascending order.
+    pub kept: Vec<usize>,
+
+    /// Indices of the elves that can be reassigned, in ascending order.
+    pub reassignable: Vec<usize>,
+
+    /// The sections covered by the schedule, as disjoint ranges in ascending
+    /// order. Adjacent sections are merged into a single range.
+    pub coverage: Vec<Range>,
+}
+
+/// optimise_schedule computes a minimum subset of the elves' assignments
+/// covering the same sections as the whole set.
+///
+/// Assignments fully contained in another one are dropped first. The ones
+/// left, ordered by their lower bound, also have increasing upper bounds,
+/// and the greedy pick of the farthest-reaching range that still connects
+/// to the sections covered so far yields an optimal cover.
+pub fn optimise_schedule(elves: &[Range]) -> Schedule {
+    let mut order: Vec<usize> = (0..elves.len()).collect();
+    order.sort_unstable_by_key(|&idx| (elves[idx].min, Reverse(elves[idx].max)));
+
+    let mut candidates: Vec<usize> = Vec::new();
+    for idx in order {
+        match candidates.last() {
+            Some(&last) if contains(&elves[last], &elves[idx]) => {}
+            _ => candidates.push(idx),
+        }
+    }
+
+    let mut schedule = Schedule::default();
+    let mut current = 0;
+    while current < candidates.len() {
+        let start = elves[candidates[current]];
+        let mut reach = start.max;
+        schedule.kept.push(candidates[current]);
+        current += 1;
+
+        loop {
+            let connected = candidates[current..]
+                .iter()
+                .take_while(|&&idx| elves[idx].min <= reach.saturating_add(1))
+                .count();
+            if connected == 0 {
+                break;
+            }
+
+            let farthest = candidates[current + connected - 1];
+            schedule.kept.push(farthest);
+            reach = elves[farthest].max;
+            current += connected;
+        }
+
+        schedule.coverage.push(Range {
+            min: start.min,
+            max: reach,
+        });
+    }
+
+    schedule.kept.sort_unstable();
+    schedule.reassignable = (0..elves.len())
+        .filter(|idx| schedule.kept.binary_search(idx).is_err())
+        .collect();
+
+    schedule
+}
+
+/// Range of sections, bounds included. Open-ended ranges extend to
+/// `i64::MIN` or `i64::MAX`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Range {
-    pub min: u64,
-    pub max: u64,
+    pub min: i64,
+    pub max: i64,
 }
 
-fn range_pair(input: &str) -> IResult<&str, (Range, Range)> {
-    context(
-        "range_pair",
-        map(separated_pair(range, tag(","), range), |(min, max)| {
-            (min, max)
-        }),
-    )(input)
+fn range_list(input: &str) -> IResult<&str, Vec<Range>> {
+    context("range_list", separated_list1(tag(","), range))(input)
 }
 
+/// range parses either a closed range such as `-3-5`, a range open on the
+/// upper end such as `5-`, or a range open on the lower end such as `-10`.
+/// Closed ranges are rejected when their bounds are reversed, as in `5-3`.
 fn range(input: &str) -> IResult<&str, Range> {
     context(
         "range",
-        map(
-            separated_pair(unsigned_integer, tag("-"), unsigned_integer),
-            |(min, max): (u64, u64)| Range { min, max },
-        ),
+        alt((
+            map(
+                verify(
+                    separated_pair(signed_integer, tag("-"), signed_integer),
+                    |(min, max)| min <= max,
+                ),
+                |(min, max)| Range { min, max },
+            ),
+            map(terminated(signed_integer, tag("-")), |min| Range {
+                min,
+                max: i64::MAX,
+            }),
+            map(preceded(tag("-"), signed_integer), |max| Range {
+                min: i64::MIN,
+                max,
+            }),
+        )),
     )(input)
 }
 
-fn unsigned_integer(input: &str) -> IResult<&str, u64> {
+fn signed_integer(input: &str) -> IResult<&str, i64> {
     context(
-        "unsigned_integer",
-        map_res(digit1, |s: &str| s.parse::<u64>()),
+        "signed_integer",
+        map_res(recognize(pair(opt(tag("-")), digit1)), |s: &str| {
+            s.parse::<i64>()
+        }),
     )(input)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(min: i64, max: i64) -> Range {
+        Range { min, max }
+    }
+
+    #[test]
+    fn test_range() {
+        assert_eq!(range("2-4"), Ok(("", r(2, 4))));
+        assert_eq!(range("-3--1"), Ok(("", r(-3, -1))));
+        assert_eq!(range("-3-5"), Ok(("", r(-3, 5))));
+        assert_eq!(range("5-"), Ok(("", r(5, i64::MAX))));
+        assert_eq!(range("-5-"), Ok(("", r(-5, i64::MAX))));
+        assert_eq!(range("-10"), Ok(("", r(i64::MIN, 10))));
+        assert_eq!(range("--10"), Ok(("", r(i64::MIN, -10))));
+    }
+
+    #[test]
+    fn test_range_list() {
+        assert_eq!(
+            range_list("2-4,6-,-1"),
+            Ok(("", vec![r(2, 4), r(6, i64::MAX), r(i64::MIN, 1)]))
+        );
+    }
+
+    #[test]
+    fn test_solve() {
+        let input =
+            input_generator_part1("2-8,3-7,4-5\n1-3,5-7,2-2\n-10,5-\n1-2,3-4,5-6\n0-").unwrap();
+
+        assert_eq!(solve_part1(&input), 2);
+        assert_eq!(solve_part2(&input), 3);
+    }
+
+    #[test]
+    fn test_parts() {
+        let assignments = parse("2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8").unwrap();
+
+        assert_eq!(assignments.lines[0], vec![r(2, 4), r(6, 8)]);
+        assert_eq!(part1(&assignments), 2);
+        assert_eq!(part2(&assignments), 4);
+        assert_eq!(
+            parse("2-4,6-8\n2-4,x").unwrap_err(),
+            ParseError::new(2, "invalid assignments \"2-4,x\"")
+        );
+        assert_eq!(
+            parse("2-4,6-8\n5-3").unwrap_err(),
+            ParseError::new(2, "invalid assignments \"5-3\"")
+        );
+        assert_eq!(
+            parse("-3--5,6-8").unwrap_err(),
+            ParseError::new(1, "invalid assignments \"-3--5,6-8\"")
+        );
+        assert!(parse("3-3,-5--3").is_ok());
+    }
+
+    #[test]
+    fn test_stream() {
+        let input = "2-8,3-7,4-5\n1-3,5-7,2-2\n-10,5-\n1-2,3-4,5-6\n0-\n";
+        let assignments = input_generator_part1(input.trim_end()).unwrap();
+
+        assert_eq!(
+            stream_part1(input.as_bytes()).unwrap(),
+            solve_part1(&assignments)
+        );
+        assert_eq!(
+            stream_part2(input.as_bytes()).unwrap(),
+            solve_part2(&assignments)
+        );
+        assert_eq!(
+            stream_part2("2-4,6-8\r\n\r\n2-6,4-8".as_bytes()).unwrap(),
+            1
+        );
+
+        let err = stream_part1("2-4,6-8\n2-4;6-8\n".as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "line 2: invalid assignments \"2-4;6-8\"");
+    }
+
+    #[test]
+    fn test_coverage() {
+        let input = vec![
+            vec![r(2, 4), r(6, 8)],
+            vec![r(2, 3), r(4, 5)],
+            vec![r(5, 7), r(7, 9)],
+            vec![r(2, 8), r(3, 7)],
+            vec![r(6, 6), r(4, 6)],
+            vec![r(2, 6), r(4, 8)],
+        ];
+        let elves = elves(&input);
+        let coverage = coverage(&elves);
+
+        for i in 0..elves.len() {
+            for j in i + 1..elves.len() {
+                assert_eq!(
+                    coverage.overlapping_pairs.contains(&(i, j)),
+                    overlapps(&elves[i], &elves[j]),
+                    "elves {i} and {j}"
+                );
+            }
+        }
+        assert_eq!(coverage.max_depth, 8);
+        assert!(coverage.uncovered.is_empty());
+    }
+
+    #[test]
+    fn test_coverage_uncovered() {
+        let coverage = coverage(&[r(10, 12), r(1, 3), r(2, 4), r(7, 7)]);
+
+        assert_eq!(coverage.overlapping_pairs, vec![(1, 2)]);
+        assert_eq!(coverage.max_depth, 2);
+        assert_eq!(coverage.uncovered, vec![r(5, 6), r(8, 9)]);
+    }
+
+    #[test]
+    fn test_optimise_schedule() {
+        let elves = [
+            r(2, 4),
+            r(6, 8),
+            r(2, 3),
+            r(4, 5),
+            r(5, 7),
+            r(3, 7),
+            r(12, 14),
+            r(13, 13),
+        ];
+        let schedule = optimise_schedule(&elves);
+
+        assert_eq!(schedule.kept, vec![0, 1, 5, 6]);
+        assert_eq!(schedule.reassignable, vec![2, 3, 4, 7]);
+        assert_eq!(schedule.coverage, vec![r(2, 8), r(12, 14)]);
+    }
+
+    #[test]
+    fn test_optimise_schedule_adjacent_and_duplicates() {
+        let elves = [r(1, 3), r(4, 6), r(1, 3), r(2, 5)];
+        let schedule = optimise_schedule(&elves);
+
+        assert_eq!(schedule.kept.len(), 2);
+        assert_eq!(schedule.coverage, vec![r(1, 6)]);
+    }
+
+    #[test]
+    fn test_coverage_open_ranges() {
+        let coverage = coverage(&[r(5, i64::MAX), r(i64::MIN, 0), r(10, i64::MAX)]);
+
+        assert_eq!(coverage.overlapping_pairs, vec![(0, 2)]);
+        assert_eq!(coverage.uncovered, vec![r(1, 4)]);
+
+        let schedule = optimise_schedule(&[r(5, i64::MAX), r(i64::MIN, 4), r(10, i64::MAX)]);
+        assert_eq!(schedule.kept, vec![0, 1]);
+        assert_eq!(schedule.coverage, vec![r(i64::MIN, i64::MAX)]);
+    }
+
+    #[test]
+    fn test_coverage_empty() {
+        assert_eq!(coverage(&[]), Coverage::default());
+    }
+}
@@ -2,91 +2,847 @@ use itertools::Itertools;
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::{alpha1, digit1, space1},
-    combinator::{map, map_res, opt, value},
-    error::context,
-    multi::{many1, separated_list1},
-    sequence::{delimited, preceded, separated_pair, terminated, tuple},
+    character::complete::{alphanumeric1, digit1, space0, space1},
+    combinator::{eof, map, map_res, opt, value},
+    error::{context, Error, ErrorKind},
+    multi::{many0, many1},
+    sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
     IResult,
 };
+use std::{
+    fmt,
+    io::{self, Write},
+    ops::Range,
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    error::ParseError,
+    solution::{self, Solution},
+};
 
 #[aoc_generator(day5)]
-pub fn input_generator_part1(input: &str) -> (Storage, Vec<Instruction>) {
-    parse(input).unwrap().1
+pub fn input_generator_part1(input: &str) -> Result<(Storage, Vec<Instruction>), ParseError> {
+    parse(input).map(|procedure| (procedure.storage, procedure.instructions))
 }
 
 #[aoc(day5, part1)]
-pub fn solve_part1(input: &(Storage, Vec<Instruction>)) -> String {
-    let game = (*input).clone();
-    let mut storage = game.0;
-    let instructions = game.1;
+pub fn solve_part1(input: &(Storage, Vec<Instruction>)) -> Result<String, InstructionError> {
+    let (mut storage, instructions) = (*input).clone();
+    Ok(operate_checked(&CrateMover9000, &mut storage, &instructions)?.tops)
+}
+
+#[aoc(day5, part2)]
+pub fn solve_part2(input: &(Storage, Vec<Instruction>)) -> Result<String, InstructionError> {
+    let (mut storage, instructions) = (*input).clone();
+    Ok(operate_checked(&CrateMover9001, &mut storage, &instructions)?.tops)
+}
+
+/// Procedure is the starting arrangement of the crates, along with the
+/// rearrangement procedure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Procedure {
+    pub storage: Storage,
+    pub instructions: Vec<Instruction>,
+}
+
+/// parse parses the stack diagram, followed by a blank line and the
+/// instructions, one per line.
+pub fn parse(input: &str) -> Result<Procedure, ParseError> {
+    let line_of = |rest: &str| input[..input.len() - rest.len()].matches('\n').count() + 1;
+
+    let (rest, (storage, instructions)) = procedure(input).map_err(|err| match err {
+        nom::Err::Error(err) | nom::Err::Failure(err) => {
+            ParseError::new(line_of(err.input), "invalid procedure")
+        }
+        nom::Err::Incomplete(_) => ParseError::new(line_of(""), "incomplete procedure"),
+    })?;
+    if !rest.trim().is_empty() {
+        return Err(ParseError::new(
+            line_of(rest),
+            format!(
+                "invalid instruction {:?}",
+                rest.lines().next().unwrap_or("")
+            ),
+        ));
+    }
+
+    Ok(Procedure {
+        storage,
+        instructions,
+    })
+}
+
+/// part1 returns the crates on top of each stack once the procedure is
+/// carried out by a CrateMover 9000, or the first instruction it cannot
+/// carry out.
+pub fn part1(procedure: &Procedure) -> Result<String, InstructionError> {
+    let mut storage = procedure.storage.clone();
+    Ok(operate_checked(&CrateMover9000, &mut storage, &procedure.instructions)?.tops)
+}
+
+/// part2 returns the crates on top of each stack once the procedure is
+/// carried out by a CrateMover 9001, or the first instruction it cannot
+/// carry out.
+pub fn part2(procedure: &Procedure) -> Result<String, InstructionError> {
+    let mut storage = procedure.storage.clone();
+    Ok(operate_checked(&CrateMover9001, &mut storage, &procedure.instructions)?.tops)
+}
+
+/// Day5 is the [`Solution`] to the supply stacks puzzle.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input = Procedure;
+    type Output = String;
+
+    fn parse(&self, input: &str) -> Result<Procedure, solution::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Procedure) -> Result<String, solution::Error> {
+        Ok(part1(input)?)
+    }
+
+    fn part2(&self, input: &Procedure) -> Result<String, solution::Error> {
+        Ok(part2(input)?)
+    }
+}
+
+/// Crane is a model of crane able to rearrange the crates of a [`Storage`].
+pub trait Crane {
+    /// execute applies a single instruction to the storage, and returns the
+    /// work it took the crane to do so. The instruction has been validated
+    /// against the storage beforehand.
+    fn execute(&self, storage: &mut Storage, instruction: &Instruction) -> Tally;
+
+    /// undo reverts the effect of executing the instruction, the storage
+    /// being in the state execute left it in. The instruction has been
+    /// validated against the reverted move beforehand.
+    fn undo(&self, storage: &mut Storage, instruction: &Instruction);
+
+    /// fuse returns what two consecutive instructions amount to, if it can
+    /// be expressed with fewer instructions for this crane. By default, only
+    /// rearrangements undoing each other are fused.
+    fn fuse(&self, first: &Instruction, second: &Instruction) -> Option<Fusion> {
+        fuse_rearrangements(first, second)
+    }
+}
+
+/// fuse_rearrangements cancels out consecutive swaps, reversals and
+/// rotations undoing each other, which doesn't depend on the crane model.
+fn fuse_rearrangements(first: &Instruction, second: &Instruction) -> Option<Fusion> {
+    let cancelled = match (&first.action, &second.action) {
+        (Action::Swap, Action::Swap) => {
+            (first.from, first.to) == (second.from, second.to)
+                || (first.from, first.to) == (second.to, second.from)
+        }
+        (Action::Reverse, Action::Reverse) | (Action::Rotate(_), Action::Rotate(_)) => {
+            *second == first.inverse()
+        }
+        _ => false,
+    };
+
+    cancelled.then_some(Fusion::Cancelled)
+}
+
+/// Fusion is what two consecutive instructions can be reduced to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fusion {
+    /// The second instruction reverts the first one.
+    Cancelled,
+
+    /// Both instructions amount to a single one.
+    Merged(Instruction),
+}
+
+/// CrateMover9000 moves crates one at a time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn execute(&self, storage: &mut Storage, instruction: &Instruction) -> Tally {
+        let quantity = instruction.quantity as usize;
+
+        match instruction.action {
+            Action::Move => {
+                for _ in 0..quantity {
+                    storage.lift(instruction.from, instruction.to, 1);
+                }
+            }
+            Action::Copy => {
+                let crates = storage.peek(instruction.from, quantity);
+                storage.drop(instruction.to, crates.into_iter().rev());
+            }
+            _ => return storage.rearrange(instruction),
+        }
+
+        Tally {
+            steps: instruction.quantity,
+            crate_moves: instruction.quantity,
+        }
+    }
 
-    for instruction in instructions.iter() {
-        for _ in 0..instruction.quantity {
-            if let Some(v) = storage.stacks[instruction.from - 1].pop() {
-                storage.stacks[instruction.to - 1].push(v)
+    fn undo(&self, storage: &mut Storage, instruction: &Instruction) {
+        match instruction.action {
+            // Moving crates back one at a time restores their original order.
+            Action::Move => {
+                self.execute(storage, &instruction.inverse());
+            }
+            Action::Copy => storage.discard(instruction.to, instruction.quantity as usize),
+            _ => {
+                storage.rearrange(&instruction.inverse());
             }
         }
     }
 
-    storage
-        .stacks
-        .iter()
-        .filter_map(|stack| stack.last())
-        .join("")
+    fn fuse(&self, first: &Instruction, second: &Instruction) -> Option<Fusion> {
+        if first.action != Action::Move || second.action != Action::Move {
+            return fuse_rearrangements(first, second);
+        }
+
+        if first.from == second.from && first.to == second.to {
+            // Crates go one at a time either way.
+            return Some(Fusion::Merged(Instruction {
+                quantity: first.quantity + second.quantity,
+                ..first.clone()
+            }));
+        }
+
+        if first.quantity == second.quantity && *second == first.inverse() {
+            return Some(Fusion::Cancelled);
+        }
+
+        None
+    }
 }
 
-#[aoc(day5, part2)]
-pub fn solve_part2(input: &(Storage, Vec<Instruction>)) -> String {
-    let game = (*input).clone();
-    let mut storage = game.0;
-    let instructions = game.1;
+/// CrateMover9001 moves any number of crates at once, keeping their order.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CrateMover9001;
 
-    for instruction in instructions.iter() {
-        let from_size = storage.stacks[instruction.from - 1].len();
-        let to_size = storage.stacks[instruction.to - 1].len();
+impl Crane for CrateMover9001 {
+    fn execute(&self, storage: &mut Storage, instruction: &Instruction) -> Tally {
+        let quantity = instruction.quantity as usize;
 
-        let values: Vec<String> = storage.stacks[instruction.from - 1]
-            .drain(from_size - instruction.quantity as usize..)
-            .collect();
+        match instruction.action {
+            Action::Move => storage.lift(instruction.from, instruction.to, quantity),
+            Action::Copy => {
+                let crates = storage.peek(instruction.from, quantity);
+                storage.drop(instruction.to, crates);
+            }
+            _ => return storage.rearrange(instruction),
+        }
 
-        values
-            .iter()
-            .for_each(|v| storage.stacks[instruction.to - 1].push(v.to_string()));
+        Tally {
+            steps: 1,
+            crate_moves: instruction.quantity,
+        }
+    }
+
+    fn undo(&self, storage: &mut Storage, instruction: &Instruction) {
+        match instruction.action {
+            Action::Move => {
+                self.execute(storage, &instruction.inverse());
+            }
+            Action::Copy => storage.discard(instruction.to, instruction.quantity as usize),
+            _ => {
+                storage.rearrange(&instruction.inverse());
+            }
+        }
+    }
+
+    fn fuse(&self, first: &Instruction, second: &Instruction) -> Option<Fusion> {
+        if first.action != Action::Move || second.action != Action::Move {
+            return fuse_rearrangements(first, second);
+        }
+
+        // The second instruction lifts exactly the block the first one
+        // dropped, and takes it further, or back where it came from.
+        if first.quantity != second.quantity || first.to != second.from {
+            return None;
+        }
+
+        if second.to == first.from {
+            return Some(Fusion::Cancelled);
+        }
+
+        Some(Fusion::Merged(Instruction {
+            to: second.to,
+            ..first.clone()
+        }))
+    }
+}
+
+/// CappedCrateMover moves several crates at once, keeping their order, but
+/// no more than its capacity: larger moves are split into batches.
+#[derive(Clone, Copy, Debug)]
+pub struct CappedCrateMover {
+    capacity: usize,
+}
+
+impl CappedCrateMover {
+    /// new returns a crane able to lift up to `capacity` crates at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "crane capacity should be at least one crate");
+        Self { capacity }
+    }
+}
+
+impl Crane for CappedCrateMover {
+    fn execute(&self, storage: &mut Storage, instruction: &Instruction) -> Tally {
+        let quantity = instruction.quantity as usize;
+
+        match instruction.action {
+            Action::Move => {
+                let mut remaining = quantity;
+                while remaining > 0 {
+                    let batch = remaining.min(self.capacity);
+                    storage.lift(instruction.from, instruction.to, batch);
+                    remaining -= batch;
+                }
+            }
+            Action::Copy => {
+                let crates = storage.peek(instruction.from, quantity);
+                for batch in crates.rchunks(self.capacity) {
+                    storage.drop(instruction.to, batch.to_vec());
+                }
+            }
+            _ => return storage.rearrange(instruction),
+        }
+
+        Tally {
+            steps: quantity.div_ceil(self.capacity) as u64,
+            crate_moves: instruction.quantity,
+        }
+    }
+
+    fn undo(&self, storage: &mut Storage, instruction: &Instruction) {
+        let quantity = instruction.quantity as usize;
+
+        match instruction.action {
+            Action::Move => {
+                // The last, possibly partial, batch ended up on top: it goes
+                // back first, followed by the full ones.
+                let mut batch = match quantity % self.capacity {
+                    0 => self.capacity,
+                    partial => partial,
+                };
+                let mut remaining = quantity;
+                while remaining > 0 {
+                    storage.lift(instruction.to, instruction.from, batch);
+                    remaining -= batch;
+                    batch = self.capacity;
+                }
+            }
+            Action::Copy => storage.discard(instruction.to, quantity),
+            _ => {
+                storage.rearrange(&instruction.inverse());
+            }
+        }
+    }
+}
+
+/// Tally counts the work done by a crane: the number of times it lifted
+/// crates, and the total number of crates it moved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub steps: u64,
+    pub crate_moves: u64,
+}
+
+impl std::ops::AddAssign for Tally {
+    fn add_assign(&mut self, other: Tally) {
+        self.steps += other.steps;
+        self.crate_moves += other.crate_moves;
+    }
+}
+
+/// Report describes the outcome of a whole rearrangement procedure.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// The crates ending up on top of each stack.
+    pub tops: String,
+
+    /// The work the crane did over the whole procedure.
+    pub tally: Tally,
+
+    /// The invalid instructions that were worked around.
+    pub warnings: Vec<InstructionError>,
+}
+
+/// operate has the crane execute every instruction against the storage,
+/// in order.
+///
+/// Invalid instructions are recorded as warnings in the report rather than
+/// aborting the procedure: instructions referring to unknown stacks are
+/// skipped, and instructions moving more crates than available only move
+/// the available ones.
+pub fn operate<C: Crane + ?Sized>(
+    crane: &C,
+    storage: &mut Storage,
+    instructions: &[Instruction],
+) -> Report {
+    let mut report = Report::default();
+
+    for (index, instruction) in instructions.iter().enumerate() {
+        let (tally, warning) = execute_lenient(crane, storage, index, instruction);
+        report.tally += tally;
+        report.warnings.extend(warning);
+    }
+
+    report.tops = storage.tops();
+    report
+}
+
+/// execute_lenient has the crane execute the instruction found at `index`
+/// in the procedure, working around it if it is invalid.
+fn execute_lenient<C: Crane + ?Sized>(
+    crane: &C,
+    storage: &mut Storage,
+    index: usize,
+    instruction: &Instruction,
+) -> (Tally, Option<InstructionError>) {
+    match storage.check(index, instruction) {
+        Ok(()) => (crane.execute(storage, instruction), None),
+        Err(warning @ InstructionError::UnknownStack { .. }) => (Tally::default(), Some(warning)),
+        Err(warning @ InstructionError::Underflow { shortfall, .. }) => {
+            let available = Instruction {
+                quantity: instruction.quantity - shortfall,
+                ..instruction.clone()
+            };
+            (crane.execute(storage, &available), Some(warning))
+        }
+    }
+}
+
+/// reverse infers the state of the storage before the procedure, from its
+/// state after the crane executed every instruction.
+///
+/// Instructions are reverted from the last one, and the first one that
+/// cannot have been executed to reach the given state is reported.
+pub fn reverse<C: Crane + ?Sized>(
+    crane: &C,
+    mut storage: Storage,
+    instructions: &[Instruction],
+) -> Result<Storage, InstructionError> {
+    for (index, instruction) in instructions.iter().enumerate().rev() {
+        storage.check(index, &instruction.inverse())?;
+        crane.undo(&mut storage, instruction);
+    }
+
+    Ok(storage)
+}
+
+/// Optimisation is a procedure shortened by [`optimise`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Optimisation {
+    /// The shortened procedure.
+    pub instructions: Vec<Instruction>,
+
+    /// The number of instructions removed from the original procedure.
+    pub removed: usize,
+}
+
+/// optimise shortens a procedure, without changing the state the crane
+/// leaves the storage in, as long as the procedure is valid for it.
+///
+/// Instructions that don't move anything are dropped, and each instruction
+/// is fused with the previous one whenever the crane allows it. Fused
+/// instructions are fused again with the ones preceding them, so that nested
+/// sequences cancelling out entirely are removed too.
+pub fn optimise<C: Crane + ?Sized>(crane: &C, instructions: &[Instruction]) -> Optimisation {
+    let mut optimised: Vec<Instruction> = Vec::with_capacity(instructions.len());
+
+    for instruction in instructions {
+        let mut current = instruction.clone();
+
+        loop {
+            if current.is_noop() {
+                break;
+            }
+
+            let fusion = optimised
+                .last()
+                .and_then(|previous| crane.fuse(previous, &current));
+
+            match fusion {
+                None => {
+                    optimised.push(current);
+                    break;
+                }
+                Some(Fusion::Cancelled) => {
+                    optimised.pop();
+                    break;
+                }
+                Some(Fusion::Merged(merged)) => {
+                    optimised.pop();
+                    current = merged;
+                }
+            }
+        }
+    }
+
+    Optimisation {
+        removed: instructions.len() - optimised.len(),
+        instructions: optimised,
     }
+}
+
+/// verify simulates both procedures against the storage, and returns whether
+/// they leave it in the same state. Either procedure being invalid for the
+/// storage is reported as an error.
+pub fn verify<C: Crane + ?Sized>(
+    crane: &C,
+    storage: &Storage,
+    original: &[Instruction],
+    optimised: &[Instruction],
+) -> Result<bool, InstructionError> {
+    let mut expected = storage.clone();
+    operate_checked(crane, &mut expected, original)?;
+
+    let mut actual = storage.clone();
+    operate_checked(crane, &mut actual, optimised)?;
+
+    Ok(expected == actual)
+}
+
+/// steps returns an iterator over the state of the storage after each
+/// instruction of the procedure. Invalid instructions are worked around the
+/// same way [`operate`] does.
+pub fn steps<'a, C: Crane + ?Sized>(
+    crane: &'a C,
+    storage: Storage,
+    instructions: &'a [Instruction],
+) -> Steps<'a, C> {
+    Steps {
+        crane,
+        storage,
+        instructions: instructions.iter().enumerate(),
+    }
+}
+
+/// Steps is the iterator returned by [`steps`].
+pub struct Steps<'a, C: ?Sized> {
+    crane: &'a C,
+    storage: Storage,
+    instructions: std::iter::Enumerate<std::slice::Iter<'a, Instruction>>,
+}
+
+impl<'a, C: Crane + ?Sized> Iterator for Steps<'a, C> {
+    type Item = Storage;
 
-    storage
-        .stacks
-        .iter()
-        .filter_map(|stack| stack.last())
-        .join("")
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, instruction) = self.instructions.next()?;
+        execute_lenient(self.crane, &mut self.storage, index, instruction);
+        Some(self.storage.clone())
+    }
 }
 
-#[derive(Clone, Debug)]
+/// animate draws the storage to `out` before the procedure starts, and
+/// after each instruction, clearing the terminal and waiting for `delay`
+/// between frames.
+pub fn animate<C: Crane + ?Sized, W: Write>(
+    crane: &C,
+    storage: Storage,
+    instructions: &[Instruction],
+    delay: Duration,
+    out: &mut W,
+) -> io::Result<()> {
+    let initial = storage.clone();
+
+    for (frame, state) in std::iter::once(initial)
+        .chain(steps(crane, storage, instructions))
+        .enumerate()
+    {
+        if frame > 0 {
+            thread::sleep(delay);
+        }
+
+        // Clear the screen and move the cursor back to its top left corner.
+        write!(out, "\x1b[2J\x1b[H")?;
+        write!(out, "{state}")?;
+        writeln!(out, "\nstep {frame}/{}", instructions.len())?;
+        out.flush()?;
+    }
+
+    Ok(())
+}
+
+/// operate_checked has the crane execute every instruction against the
+/// storage, in order, and stops at the first invalid one.
+pub fn operate_checked<C: Crane + ?Sized>(
+    crane: &C,
+    storage: &mut Storage,
+    instructions: &[Instruction],
+) -> Result<Report, InstructionError> {
+    let mut report = Report::default();
+
+    for (index, instruction) in instructions.iter().enumerate() {
+        storage.check(index, instruction)?;
+        report.tally += crane.execute(storage, instruction);
+    }
+
+    report.tops = storage.tops();
+    Ok(report)
+}
+
+/// InstructionError describes why an instruction cannot be executed against
+/// a storage. Instructions are indexed from 0, stacks from 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InstructionError {
+    /// The instruction refers to a stack the storage doesn't have.
+    UnknownStack { instruction: usize, stack: usize },
+
+    /// The instruction moves more crates than the stack holds.
+    Underflow {
+        instruction: usize,
+        stack: usize,
+        shortfall: u64,
+    },
+}
+
+impl fmt::Display for InstructionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstructionError::UnknownStack { instruction, stack } => {
+                write!(f, "instruction {instruction}: stack {stack} does not exist")
+            }
+            InstructionError::Underflow {
+                instruction,
+                stack,
+                shortfall,
+            } => write!(
+                f,
+                "instruction {instruction}: stack {stack} is {shortfall} crate(s) short"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InstructionError {}
+
+/// Storage holds the stacks of crates, numbered from 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Storage {
-    stacks: Vec<Stack>,
+    /// The crates of each stack, from the bottom one.
+    pub stacks: Vec<Stack>,
+}
+
+/// Storage is drawn the same way the puzzle input does, including the index
+/// footer and its trailing newline, so that the drawing can be parsed back.
+/// Columns are widened as needed to fit the longest label and index.
+///
+/// A storage without any stack is the exception: it is drawn as a blank row
+/// over a blank footer, which doesn't parse back since the footer lists no
+/// index.
+impl fmt::Display for Storage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // An empty storage still gets a row, as the parser expects one.
+        let height = self.stacks.iter().map(Vec::len).max().unwrap_or(0).max(1);
+        let width = self
+            .stacks
+            .iter()
+            .flatten()
+            .map(|c| c.len() + 2)
+            .chain([3, self.stacks.len().to_string().len()])
+            .max()
+            .unwrap_or(3);
+
+        for level in (0..height).rev() {
+            let row = self
+                .stacks
+                .iter()
+                .map(|stack| match stack.get(level) {
+                    Some(c) => format!("{:^width$}", format!("[{c}]")),
+                    None => " ".repeat(width),
+                })
+                .join(" ");
+            writeln!(f, "{row}")?;
+        }
+
+        let footer = (1..=self.stacks.len())
+            .map(|idx| format!("{idx:^width$}"))
+            .join(" ");
+        writeln!(f, "{footer}")
+    }
 }
 
-type Stack = Vec<Crate>;
-type Crate = String;
+impl Storage {
+    /// tops returns the crates on top of each stack, skipping empty stacks.
+    pub fn tops(&self) -> String {
+        self.stacks.iter().filter_map(|stack| stack.last()).join("")
+    }
+
+    /// check verifies that the instruction, found at `index` in the
+    /// procedure, can be executed against the storage in its current state.
+    fn check(&self, index: usize, instruction: &Instruction) -> Result<(), InstructionError> {
+        let stacks = match instruction.action {
+            Action::Move | Action::Copy | Action::Swap => vec![instruction.from, instruction.to],
+            Action::Reverse => vec![instruction.from],
+            Action::Rotate(_) => return Ok(()),
+        };
+
+        for stack in stacks {
+            if stack == 0 || stack > self.stacks.len() {
+                return Err(InstructionError::UnknownStack {
+                    instruction: index,
+                    stack,
+                });
+            }
+        }
 
-#[derive(Clone, Debug)]
+        let available = self.stacks[instruction.from - 1].len() as u64;
+        if instruction.action != Action::Swap && instruction.quantity > available {
+            return Err(InstructionError::Underflow {
+                instruction: index,
+                stack: instruction.from,
+                shortfall: instruction.quantity - available,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// lift moves the top `quantity` crates of the `from` stack onto the `to`
+    /// stack at once, keeping their order. Stacks are numbered from 1.
+    fn lift(&mut self, from: usize, to: usize, quantity: usize) {
+        let from_size = self.stacks[from - 1].len();
+        let crates: Vec<Crate> = self.stacks[from - 1]
+            .drain(from_size - quantity..)
+            .collect();
+
+        self.stacks[to - 1].extend(crates);
+    }
+
+    /// peek returns a copy of the top `quantity` crates of the stack, from
+    /// the lowest to the topmost one.
+    fn peek(&self, stack: usize, quantity: usize) -> Vec<Crate> {
+        let size = self.stacks[stack - 1].len();
+        self.stacks[stack - 1][size - quantity..].to_vec()
+    }
+
+    /// drop puts the crates on top of the stack, in order.
+    fn drop(&mut self, stack: usize, crates: impl IntoIterator<Item = Crate>) {
+        self.stacks[stack - 1].extend(crates);
+    }
+
+    /// discard removes the top `quantity` crates of the stack.
+    fn discard(&mut self, stack: usize, quantity: usize) {
+        let size = self.stacks[stack - 1].len();
+        self.stacks[stack - 1].truncate(size - quantity);
+    }
+
+    /// rearrange executes the instructions that rearrange crates the same way
+    /// regardless of the crane: swaps, reversals and rotations. Each counts
+    /// as a single step, moving every crate whose position changed.
+    fn rearrange(&mut self, instruction: &Instruction) -> Tally {
+        let crate_moves = match instruction.action {
+            Action::Swap if instruction.from == instruction.to => 0,
+            Action::Swap => {
+                self.stacks.swap(instruction.from - 1, instruction.to - 1);
+                self.stacks[instruction.from - 1].len() + self.stacks[instruction.to - 1].len()
+            }
+            Action::Reverse => {
+                let quantity = instruction.quantity as usize;
+                let size = self.stacks[instruction.from - 1].len();
+                self.stacks[instruction.from - 1][size - quantity..].reverse();
+                quantity
+            }
+            Action::Rotate(rotation) if !self.stacks.is_empty() => {
+                let shift = instruction.quantity as usize % self.stacks.len();
+                match rotation {
+                    Rotation::Left => self.stacks.rotate_left(shift),
+                    Rotation::Right => self.stacks.rotate_right(shift),
+                }
+                self.stacks.iter().map(Vec::len).sum()
+            }
+            Action::Rotate(_) => 0,
+            Action::Move | Action::Copy => unreachable!("not a rearrangement"),
+        };
+
+        Tally {
+            steps: 1,
+            crate_moves: crate_moves as u64,
+        }
+    }
+}
+
+pub type Stack = Vec<Crate>;
+pub type Crate = String;
+
+/// Instruction of the rearrangement procedure. How `quantity`, `from` and
+/// `to` are read depends on its action:
+///
+/// * `move` and `copy` carry `quantity` crates from the `from` stack onto
+///   the `to` stack.
+/// * `swap` exchanges the `from` and `to` stacks, `quantity` being unused.
+/// * `reverse` reverses the order of the top `quantity` crates of the `from`
+///   stack, `to` being the same stack.
+/// * `rotate` cycles the stacks by `quantity` positions, `from` and `to`
+///   being unused.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Instruction {
-    action: Action,
-    quantity: u64,
-    from: usize,
-    to: usize,
+    pub action: Action,
+    pub quantity: u64,
+    pub from: usize,
+    pub to: usize,
 }
 
-#[derive(Clone, Debug)]
+impl Instruction {
+    /// is_noop returns whether the instruction leaves the storage untouched.
+    fn is_noop(&self) -> bool {
+        match self.action {
+            Action::Move => self.quantity == 0 || self.from == self.to,
+            Action::Copy | Action::Rotate(_) => self.quantity == 0,
+            Action::Swap => self.from == self.to,
+            Action::Reverse => self.quantity <= 1,
+        }
+    }
+
+    /// inverse returns the instruction moving the same crates back. Reversals
+    /// are their own inverse, and rotations are inverted by rotating the
+    /// other way around.
+    fn inverse(&self) -> Instruction {
+        let action = match self.action {
+            Action::Rotate(Rotation::Left) => Action::Rotate(Rotation::Right),
+            Action::Rotate(Rotation::Right) => Action::Rotate(Rotation::Left),
+            action => action,
+        };
+
+        Instruction {
+            action,
+            from: self.to,
+            to: self.from,
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Action {
     Move,
+    Copy,
+    Swap,
+    Reverse,
+    Rotate(Rotation),
 }
 
-// FIXME: For some reason I don't have time to look into, the parser
-// doesn't parse the last line, so one needs to add a dummy one at the end
-// of the file.
-fn parse(input: &str) -> IResult<&str, (Storage, Vec<Instruction>)> {
+/// Rotation is the direction the stacks are cycled in: rotating left moves
+/// the first stack to the last position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    Left,
+    Right,
+}
+
+fn procedure(input: &str) -> IResult<&str, (Storage, Vec<Instruction>)> {
     context(
         "parser",
         terminated(
@@ -96,57 +852,103 @@ fn parse(input: &str) -> IResult<&str, (Storage, Vec<Instruction>)> {
     )(input)
 }
 
+/// storage parses the stack diagram. Crates are assigned to the stack whose
+/// index, in the footer, sits under them, so that labels and indices can be
+/// any width.
 fn storage(input: &str) -> IResult<&str, Storage> {
-    let (remain, store) = terminated(separated_list1(tag("\n"), storage_line), tag("\n"))(input)?;
-    let (remain, indices) = delimited(
-        tag(" "),
-        separated_list1(tag("   "), unsigned_size),
-        tag(" \n"),
-    )(remain)?;
+    let (remain, rows) = many1(terminated(
+        |line| map(storage_line, |row| (line, row))(line),
+        tag("\n"),
+    ))(input)?;
+    let (remain, indices) = terminated(storage_footer, tag("\n"))(remain)?;
 
     let mut storage = Storage {
         stacks: vec![vec![]; indices.len()],
     };
-    for row in store.iter().rev() {
-        for (idx, value) in row.iter().enumerate() {
-            if let Some(v) = value {
-                storage.stacks[idx].push(v.to_string())
-            }
+    for (line, row) in rows.into_iter().rev() {
+        for (span, value) in row {
+            let misplaced =
+                || nom::Err::Failure(Error::new(&line[span.start..], ErrorKind::Verify));
+            // A crate must sit over exactly one index: none, and it belongs
+            // to no stack, several, and it could belong to either.
+            let stack = indices
+                .iter()
+                .positions(|index| index.start < span.end && span.start < index.end)
+                .exactly_one()
+                .map_err(|_| misplaced())?;
+
+            storage.stacks[stack].push(value)
         }
     }
 
     Ok((remain, storage))
 }
 
-fn storage_line(input: &str) -> IResult<&str, Vec<Option<Crate>>> {
+/// storage_line parses a row of the stack diagram into its crates, along with
+/// the columns they span.
+fn storage_line(input: &str) -> IResult<&str, Vec<(Range<usize>, Crate)>> {
     context(
         "storage_line",
-        separated_list1(
-            tag(" "),
-            alt((map(tag("   "), |t| None), map(parse_crate, Some))),
+        terminated(
+            many0(preceded(space0, |i| spanned(input, parse_crate, i))),
+            space0,
         ),
     )(input)
 }
 
+/// storage_footer parses the stack indices under the diagram into the
+/// columns they span.
+fn storage_footer(input: &str) -> IResult<&str, Vec<Range<usize>>> {
+    context(
+        "storage_footer",
+        terminated(
+            many1(preceded(space0, |i| {
+                map(|i| spanned(input, unsigned_size, i), |(span, _)| span)(i)
+            })),
+            space0,
+        ),
+    )(input)
+}
+
+/// spanned applies the parser to `input`, a suffix of `line`, and returns the
+/// columns of `line` spanned by what it consumed.
+fn spanned<'a, O>(
+    line: &'a str,
+    mut parser: impl FnMut(&'a str) -> IResult<&'a str, O>,
+    input: &'a str,
+) -> IResult<&'a str, (Range<usize>, O)> {
+    let (remain, output) = parser(input)?;
+    let start = line.len() - input.len();
+    let end = line.len() - remain.len();
+
+    Ok((remain, (start..end, output)))
+}
+
 fn parse_crate(input: &str) -> IResult<&str, Crate> {
     context(
         "crate",
-        map(delimited(tag("["), alpha1, tag("]")), String::from),
+        map(delimited(tag("["), alphanumeric1, tag("]")), String::from),
     )(input)
 }
 
 fn instruction(input: &str) -> IResult<&str, Instruction> {
     context(
         "instruction",
+        terminated(
+            alt((transfer, swap, reversal, rotation)),
+            alt((tag("\n"), eof)),
+        ),
+    )(input)
+}
+
+fn transfer(input: &str) -> IResult<&str, Instruction> {
+    context(
+        "transfer",
         map(
             tuple((
                 separated_pair(action, space1, unsigned_integer_64),
                 preceded(delimited(space1, tag("from"), space1), unsigned_size),
-                delimited(
-                    delimited(space1, tag("to"), space1),
-                    unsigned_size,
-                    tag("\n"),
-                ),
+                preceded(delimited(space1, tag("to"), space1), unsigned_size),
             )),
             |((action, quantity), from, to)| Instruction {
                 action,
@@ -159,7 +961,71 @@ fn instruction(input: &str) -> IResult<&str, Instruction> {
 }
 
 fn action(input: &str) -> IResult<&str, Action> {
-    context("action", value(Action::Move, tag("move")))(input)
+    context(
+        "action",
+        alt((
+            value(Action::Move, tag("move")),
+            value(Action::Copy, tag("copy")),
+        )),
+    )(input)
+}
+
+fn swap(input: &str) -> IResult<&str, Instruction> {
+    context(
+        "swap",
+        map(
+            preceded(
+                pair(tag("swap"), space1),
+                separated_pair(unsigned_size, space1, unsigned_size),
+            ),
+            |(from, to)| Instruction {
+                action: Action::Swap,
+                quantity: 0,
+                from,
+                to,
+            },
+        ),
+    )(input)
+}
+
+fn reversal(input: &str) -> IResult<&str, Instruction> {
+    context(
+        "reversal",
+        map(
+            separated_pair(
+                preceded(pair(tag("reverse"), space1), unsigned_integer_64),
+                delimited(space1, tag("on"), space1),
+                unsigned_size,
+            ),
+            |(quantity, stack)| Instruction {
+                action: Action::Reverse,
+                quantity,
+                from: stack,
+                to: stack,
+            },
+        ),
+    )(input)
+}
+
+fn rotation(input: &str) -> IResult<&str, Instruction> {
+    context(
+        "rotation",
+        map(
+            preceded(
+                pair(tag("rotate"), space1),
+                alt((
+                    value(Rotation::Left, tag("left")),
+                    value(Rotation::Right, tag("right")),
+                )),
+            ),
+            |rotation| Instruction {
+                action: Action::Rotate(rotation),
+                quantity: 1,
+                from: 0,
+                to: 0,
+            },
+        ),
+    )(input)
 }
 
 fn unsigned_size(input: &str) -> IResult<&str, usize> {
@@ -175,3 +1041,478 @@ fn unsigned_integer_64(input: &str) -> IResult<&str, u64> {
         map_res(digit1, |s: &str| s.parse::<u64>()),
     )(input)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2
+";
+
+    #[test]
+    fn test_operate() {
+        let (storage, instructions) = input_generator_part1(EXAMPLE).unwrap();
+
+        let report = operate(&CrateMover9000, &mut storage.clone(), &instructions);
+        assert_eq!(report.tops, "CMZ");
+        assert_eq!(
+            report.tally,
+            Tally {
+                steps: 7,
+                crate_moves: 7
+            }
+        );
+
+        let report = operate(&CrateMover9001, &mut storage.clone(), &instructions);
+        assert_eq!(report.tops, "MCD");
+        assert_eq!(
+            report.tally,
+            Tally {
+                steps: 4,
+                crate_moves: 7
+            }
+        );
+
+        let report = operate(
+            &CappedCrateMover::new(2),
+            &mut storage.clone(),
+            &instructions,
+        );
+        assert_eq!(report.tops, "MCZ");
+        assert_eq!(
+            report.tally,
+            Tally {
+                steps: 5,
+                crate_moves: 7
+            }
+        );
+    }
+
+    #[test]
+    fn test_operate_invalid_instructions() {
+        let (storage, _) = input_generator_part1(EXAMPLE).unwrap();
+        let instructions = vec![mv(1, 1, 1), mv(3, 3, 1), mv(1, 4, 1), mv(1, 1, 0)];
+
+        for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001] {
+            assert_eq!(
+                operate_checked(crane, &mut storage.clone(), &instructions),
+                Err(InstructionError::Underflow {
+                    instruction: 1,
+                    stack: 3,
+                    shortfall: 2,
+                })
+            );
+
+            let report = operate(crane, &mut storage.clone(), &instructions);
+            assert_eq!(report.tops, "PD");
+            assert_eq!(report.tally.crate_moves, 2);
+            assert_eq!(
+                report.warnings,
+                vec![
+                    InstructionError::Underflow {
+                        instruction: 1,
+                        stack: 3,
+                        shortfall: 2
+                    },
+                    InstructionError::UnknownStack {
+                        instruction: 2,
+                        stack: 4
+                    },
+                    InstructionError::UnknownStack {
+                        instruction: 3,
+                        stack: 0
+                    },
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_reverse() {
+        let (storage, instructions) = input_generator_part1(EXAMPLE).unwrap();
+        let cranes: [&dyn Crane; 4] = [
+            &CrateMover9000,
+            &CrateMover9001,
+            &CappedCrateMover::new(2),
+            &CappedCrateMover::new(3),
+        ];
+
+        for crane in cranes {
+            let mut last = storage.clone();
+            operate_checked(crane, &mut last, &instructions).unwrap();
+
+            assert_eq!(reverse(crane, last, &instructions), Ok(storage.clone()));
+        }
+    }
+
+    #[test]
+    fn test_reverse_impossible() {
+        let (storage, instructions) = input_generator_part1(EXAMPLE).unwrap();
+
+        assert_eq!(
+            reverse(&CrateMover9000, storage, &instructions),
+            Err(InstructionError::Underflow {
+                instruction: 1,
+                stack: 3,
+                shortfall: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_optimise_9001() {
+        let (storage, _) = input_generator_part1(EXAMPLE).unwrap();
+        let instructions = vec![
+            mv(1, 2, 1),
+            mv(2, 1, 3),
+            mv(1, 3, 3),
+            mv(0, 2, 1),
+            mv(2, 3, 2),
+            mv(2, 2, 1),
+            mv(1, 2, 1),
+        ];
+
+        let optimisation = optimise(&CrateMover9001, &instructions);
+        assert_eq!(optimisation.instructions, vec![mv(1, 2, 1), mv(1, 2, 1)]);
+        assert_eq!(optimisation.removed, 5);
+        assert_eq!(
+            verify(
+                &CrateMover9001,
+                &storage,
+                &instructions,
+                &optimisation.instructions
+            ),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_optimise_9000() {
+        let (storage, _) = input_generator_part1(EXAMPLE).unwrap();
+        let instructions = vec![
+            mv(1, 2, 1),
+            mv(1, 2, 1),
+            mv(2, 1, 2),
+            mv(1, 2, 3),
+            mv(1, 2, 3),
+        ];
+
+        let optimisation = optimise(&CrateMover9000, &instructions);
+        assert_eq!(optimisation.instructions, vec![mv(2, 2, 3)]);
+        assert_eq!(optimisation.removed, 4);
+        assert_eq!(
+            verify(
+                &CrateMover9000,
+                &storage,
+                &instructions,
+                &optimisation.instructions
+            ),
+            Ok(true)
+        );
+        assert_eq!(
+            optimise(&CrateMover9001, &[mv(1, 2, 3), mv(1, 2, 3)]).removed,
+            0
+        );
+    }
+
+    #[test]
+    fn test_optimise_capped() {
+        let (_, instructions) = input_generator_part1(EXAMPLE).unwrap();
+        let instructions = [instructions, vec![mv(3, 3, 1)]].concat();
+
+        let optimisation = optimise(&CappedCrateMover::new(2), &instructions);
+        assert_eq!(optimisation.instructions, instructions);
+        assert_eq!(optimisation.removed, 0);
+    }
+
+    #[test]
+    fn test_storage_display() {
+        let (storage, _) = input_generator_part1(EXAMPLE).unwrap();
+
+        assert_eq!(
+            storage.to_string(),
+            EXAMPLE.split("\n\n").next().unwrap().to_string() + "\n"
+        );
+
+        let empty_stacks = Storage {
+            stacks: vec![vec![]; 3],
+        };
+        assert_eq!(empty_stacks.to_string(), "           \n 1   2   3 \n");
+        assert_eq!(
+            super::storage(&empty_stacks.to_string()),
+            Ok(("", empty_stacks))
+        );
+
+        let no_stacks = Storage { stacks: vec![] };
+        assert_eq!(no_stacks.to_string(), "\n\n");
+        assert!(super::storage(&no_stacks.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_storage_wide() {
+        let drawing = [
+            "                                             [K]",
+            "[AB]                [1]                      [L]",
+            "[C]  [D2]           [Q]                      [M]  [X]",
+            " 1    2    3    4    5    6    7    8    9    10   11",
+            "",
+        ]
+        .join("\n");
+        let (remain, parsed) = storage(&drawing).unwrap();
+
+        assert_eq!(remain, "");
+        assert_eq!(parsed.stacks.len(), 11);
+        assert_eq!(parsed.stacks[0], vec!["C", "AB"]);
+        assert_eq!(parsed.stacks[1], vec!["D2"]);
+        assert_eq!(parsed.stacks[4], vec!["Q", "1"]);
+        assert_eq!(parsed.stacks[9], vec!["M", "L", "K"]);
+        assert_eq!(parsed.stacks[10], vec!["X"]);
+        assert_eq!(storage(&parsed.to_string()), Ok(("", parsed)));
+    }
+
+    #[test]
+    fn test_storage_misaligned_crate() {
+        assert!(storage("      [A]\n 1   2 \n").is_err());
+    }
+
+    #[test]
+    fn test_storage_ambiguous_crate() {
+        assert_eq!(
+            storage("[ABC]\n 1 2 \n"),
+            Err(nom::Err::Failure(Error::new(
+                "[ABC]\n 1 2 \n",
+                ErrorKind::Verify
+            )))
+        );
+        assert_eq!(
+            parse("[A]\n[BCD]\n 1 2\n\nmove 1 from 1 to 2").unwrap_err(),
+            ParseError::new(2, "invalid procedure")
+        );
+    }
+
+    #[test]
+    fn test_steps_round_trip() {
+        let (storage, instructions) = input_generator_part1(EXAMPLE).unwrap();
+
+        let states = steps(&CrateMover9001, storage, &instructions).collect_vec();
+        assert_eq!(states.len(), instructions.len());
+        assert_eq!(states.last().unwrap().tops(), "MCD");
+
+        for state in states {
+            let drawing = state.to_string();
+            assert_eq!(super::storage(&drawing), Ok(("", state)));
+        }
+    }
+
+    #[test]
+    fn test_animate() {
+        let (storage, instructions) = input_generator_part1(EXAMPLE).unwrap();
+        let mut out = Vec::new();
+
+        animate(
+            &CrateMover9000,
+            storage,
+            &instructions,
+            Duration::ZERO,
+            &mut out,
+        )
+        .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.matches("\x1b[2J").count(), instructions.len() + 1);
+        assert!(out.ends_with("[C] [M] [P]\n 1   2   3 \n\nstep 4/4\n"));
+    }
+
+    #[test]
+    fn test_parts() {
+        let procedure = parse(EXAMPLE.trim_end()).unwrap();
+
+        assert_eq!(procedure.storage.stacks[1], vec!["M", "C", "D"]);
+        assert_eq!(procedure.instructions.len(), 4);
+        assert_eq!(parse(EXAMPLE).unwrap(), procedure);
+        assert_eq!(part1(&procedure).unwrap(), "CMZ");
+        assert_eq!(part2(&procedure).unwrap(), "MCD");
+
+        let underflow =
+            parse(&EXAMPLE.replace("move 3 from 1 to 3", "move 4 from 1 to 3")).unwrap();
+        assert_eq!(
+            part1(&underflow).unwrap_err(),
+            InstructionError::Underflow {
+                instruction: 1,
+                stack: 1,
+                shortfall: 1
+            }
+        );
+        assert_eq!(
+            part2(&underflow).unwrap_err(),
+            part1(&underflow).unwrap_err()
+        );
+        assert!(Day5.part1(&underflow).is_err());
+
+        let invalid = EXAMPLE.replace("move 2 from 2 to 1", "move 2 to 1");
+        assert_eq!(
+            parse(&invalid).unwrap_err(),
+            ParseError::new(8, "invalid instruction \"move 2 to 1\"")
+        );
+        assert_eq!(
+            parse("[B]\n[A]     [C]\n 1   2 \n\nmove 1 from 1 to 2").unwrap_err(),
+            ParseError::new(2, "invalid procedure")
+        );
+    }
+
+    #[test]
+    fn test_instruction() {
+        let parse = |line: &str| {
+            instruction(&format!("{line}\n"))
+                .map(|(_, i)| i)
+                .map_err(|e| e.to_string())
+        };
+
+        assert_eq!(parse("move 3 from 1 to 2"), Ok(mv(3, 1, 2)));
+        assert_eq!(parse("copy 3 from 1 to 2"), Ok(cp(3, 1, 2)));
+        assert_eq!(parse("swap 1 3"), Ok(swap_stacks(1, 3)));
+        assert_eq!(parse("reverse 2 on 3"), Ok(rev(2, 3)));
+        assert_eq!(parse("rotate left"), Ok(rot(Rotation::Left)));
+        assert_eq!(parse("rotate right"), Ok(rot(Rotation::Right)));
+        assert!(parse("rotate up").is_err());
+        assert!(parse("swap 1").is_err());
+    }
+
+    #[test]
+    fn test_operate_swap() {
+        for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001] {
+            assert_eq!(run(crane, &[swap_stacks(1, 3)]), "P|MCD|ZN");
+        }
+    }
+
+    #[test]
+    fn test_operate_reverse() {
+        for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001] {
+            assert_eq!(run(crane, &[rev(2, 2)]), "ZN|MDC|P");
+            assert_eq!(run(crane, &[rev(3, 2)]), "ZN|DCM|P");
+        }
+    }
+
+    #[test]
+    fn test_operate_rotate() {
+        for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001] {
+            assert_eq!(run(crane, &[rot(Rotation::Left)]), "MCD|P|ZN");
+            assert_eq!(run(crane, &[rot(Rotation::Right)]), "P|ZN|MCD");
+        }
+    }
+
+    #[test]
+    fn test_operate_copy() {
+        assert_eq!(run(&CrateMover9000, &[cp(2, 2, 1)]), "ZNDC|MCD|P");
+        assert_eq!(run(&CrateMover9001, &[cp(2, 2, 1)]), "ZNCD|MCD|P");
+        assert_eq!(run(&CrateMover9000, &[cp(2, 2, 2)]), "ZN|MCDDC|P");
+        assert_eq!(run(&CrateMover9001, &[cp(2, 2, 2)]), "ZN|MCDCD|P");
+        assert_eq!(
+            run(&CappedCrateMover::new(2), &[cp(3, 2, 3)]),
+            "ZN|MCD|PCDM"
+        );
+    }
+
+    #[test]
+    fn test_reverse_rearrangements() {
+        let (storage, _) = input_generator_part1(EXAMPLE).unwrap();
+        let instructions = vec![
+            cp(2, 2, 1),
+            swap_stacks(1, 2),
+            rev(3, 2),
+            rot(Rotation::Left),
+            mv(2, 1, 3),
+            rot(Rotation::Right),
+        ];
+        let cranes: [&dyn Crane; 3] = [&CrateMover9000, &CrateMover9001, &CappedCrateMover::new(2)];
+
+        for crane in cranes {
+            let mut last = storage.clone();
+            operate_checked(crane, &mut last, &instructions).unwrap();
+
+            assert_eq!(reverse(crane, last, &instructions), Ok(storage.clone()));
+        }
+    }
+
+    #[test]
+    fn test_optimise_rearrangements() {
+        let instructions = vec![
+            swap_stacks(1, 2),
+            rot(Rotation::Left),
+            rev(2, 3),
+            rev(2, 3),
+            rot(Rotation::Right),
+            swap_stacks(2, 1),
+            rev(1, 1),
+            swap_stacks(3, 3),
+            cp(1, 2, 2),
+        ];
+
+        for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001] {
+            let optimisation = optimise(crane, &instructions);
+            assert_eq!(optimisation.instructions, vec![cp(1, 2, 2)]);
+        }
+    }
+
+    /// run executes the instructions against the example storage, and
+    /// returns its stacks, bottom to top, separated by a pipe.
+    fn run(crane: &dyn Crane, instructions: &[Instruction]) -> String {
+        let (mut storage, _) = input_generator_part1(EXAMPLE).unwrap();
+        operate_checked(crane, &mut storage, instructions).unwrap();
+
+        storage.stacks.iter().map(|stack| stack.join("")).join("|")
+    }
+
+    fn mv(quantity: u64, from: usize, to: usize) -> Instruction {
+        Instruction {
+            action: Action::Move,
+            quantity,
+            from,
+            to,
+        }
+    }
+
+    fn cp(quantity: u64, from: usize, to: usize) -> Instruction {
+        Instruction {
+            action: Action::Copy,
+            quantity,
+            from,
+            to,
+        }
+    }
+
+    fn swap_stacks(from: usize, to: usize) -> Instruction {
+        Instruction {
+            action: Action::Swap,
+            quantity: 0,
+            from,
+            to,
+        }
+    }
+
+    fn rev(quantity: u64, stack: usize) -> Instruction {
+        Instruction {
+            action: Action::Reverse,
+            quantity,
+            from: stack,
+            to: stack,
+        }
+    }
+
+    fn rot(rotation: Rotation) -> Instruction {
+        Instruction {
+            action: Action::Rotate(rotation),
+            quantity: 1,
+            from: 0,
+            to: 0,
+        }
+    }
+}
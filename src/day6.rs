@@ -1,26 +1,740 @@
 use itertools::Itertools;
+use rand::{seq::SliceRandom, Rng};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, BufRead, Bytes},
+    iter::FusedIterator,
+};
+
+use crate::{
+    error::ParseError,
+    solution::{self, Solution},
+};
 
 #[aoc_generator(day6)]
 pub fn input_generator_part1(input: &str) -> Vec<char> {
-    include_str!("../input/2022/day6.txt").chars().collect_vec()
+    input.chars().collect_vec()
 }
 
 #[aoc(day6, part1)]
 pub fn solve_part1(input: &[char]) -> u64 {
-    input
-        .windows(4)
-        .into_iter()
-        .position(|w| w.iter().unique().count() == w.len())
-        .map(|pos| pos as u64 + 4)
-        .expect("should have found a message")
+    find_marker(input, 4).expect("should have found a message")
 }
 
 #[aoc(day6, part2)]
 pub fn solve_part2(input: &[char]) -> u64 {
+    find_marker(input, 14).expect("should have found a message")
+}
+
+/// find_marker returns the end position of the first marker of `size`
+/// distinct characters in the signal, checking each window in turn.
+fn find_marker(input: &[char], size: usize) -> Option<u64> {
     input
-        .windows(14)
-        .into_iter()
+        .windows(size)
         .position(|w| w.iter().unique().count() == w.len())
-        .map(|pos| pos as u64 + 14)
-        .expect("should have found a message")
+        .map(|pos| (pos + size) as u64)
+}
+
+/// Datastream is the signal received by the communication device.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Datastream {
+    pub signal: Vec<u8>,
+}
+
+/// parse parses the signal, a single line of printable ASCII characters.
+pub fn parse(input: &str) -> Result<Datastream, ParseError> {
+    let signal = input.trim_end();
+    if let Some((idx, c)) = signal.char_indices().find(|(_, c)| !c.is_ascii_graphic()) {
+        return Err(ParseError::new(
+            signal[..idx].matches('\n').count() + 1,
+            format!("unexpected character {c:?} at offset {idx}"),
+        ));
+    }
+
+    Ok(Datastream {
+        signal: signal.as_bytes().to_vec(),
+    })
+}
+
+/// part1 returns the end position of the first start-of-packet marker, if
+/// any.
+pub fn part1(datastream: &Datastream) -> Option<u64> {
+    markers(4, datastream.signal.iter().copied())
+        .next()
+        .map(|pos| pos as u64)
+}
+
+/// part2 returns the end position of the first start-of-message marker, if
+/// any.
+pub fn part2(datastream: &Datastream) -> Option<u64> {
+    markers(14, datastream.signal.iter().copied())
+        .next()
+        .map(|pos| pos as u64)
+}
+
+/// Day6 is the [`Solution`] to the tuning trouble puzzle.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Day6;
+
+impl Solution for Day6 {
+    type Input = Datastream;
+    type Output = u64;
+
+    fn parse(&self, input: &str) -> Result<Datastream, solution::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Datastream) -> Result<u64, solution::Error> {
+        part1(input).ok_or_else(|| "no start-of-packet marker".into())
+    }
+
+    fn part2(&self, input: &Datastream) -> Result<u64, solution::Error> {
+        part2(input).ok_or_else(|| "no start-of-message marker".into())
+    }
+}
+
+/// stream_part1 solves part 1 from the signal read from `reader`, one byte
+/// at a time.
+pub fn stream_part1<R: BufRead>(reader: R) -> io::Result<u64> {
+    first_marker(4, reader)
+}
+
+/// stream_part2 solves part 2 from the signal read from `reader`, one byte
+/// at a time.
+pub fn stream_part2<R: BufRead>(reader: R) -> io::Result<u64> {
+    first_marker(14, reader)
+}
+
+/// first_marker returns the end position of the first marker of `size`
+/// distinct bytes in the signal read from `reader`. Reaching the end of the
+/// signal without finding one is an [`io::ErrorKind::UnexpectedEof`] error.
+fn first_marker<R: BufRead>(size: usize, reader: R) -> io::Result<u64> {
+    match read_markers(size, reader).next() {
+        Some(position) => position.map(|position| position as u64),
+        None => Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("no marker of {size} distinct bytes"),
+        )),
+    }
+}
+
+/// MarkerDetector looks for markers in a signal fed to it one byte at a time:
+/// positions where the last `size` bytes are all different.
+///
+/// It keeps the bytes of the current window in a ring buffer, along with how
+/// many times each byte value occurs in it, and how many byte values occur
+/// more than once. Each byte is thus processed in constant time and memory,
+/// however long the signal.
+#[derive(Clone, Debug)]
+pub struct MarkerDetector {
+    size: usize,
+    window: Vec<u8>,
+    counts: [usize; 256],
+    duplicates: usize,
+    position: usize,
+}
+
+impl MarkerDetector {
+    /// new returns a detector for markers of `size` distinct bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0, "markers should be at least one byte long");
+
+        Self {
+            size,
+            window: vec![0; size],
+            counts: [0; 256],
+            duplicates: 0,
+            position: 0,
+        }
+    }
+
+    /// push feeds the next byte of the signal to the detector, and returns
+    /// the number of bytes processed so far if they end with a marker.
+    pub fn push(&mut self, byte: u8) -> Option<usize> {
+        let slot = self.position % self.size;
+
+        if self.position >= self.size {
+            let leaving = self.window[slot] as usize;
+            self.counts[leaving] -= 1;
+            if self.counts[leaving] == 1 {
+                self.duplicates -= 1;
+            }
+        }
+
+        self.window[slot] = byte;
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 2 {
+            self.duplicates += 1;
+        }
+
+        self.position += 1;
+        (self.position >= self.size && self.duplicates == 0).then_some(self.position)
+    }
+
+    /// position returns the number of bytes processed so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+/// markers returns an iterator over the end positions of every marker of
+/// `size` distinct bytes in the signal, as they are found.
+pub fn markers<I: IntoIterator<Item = u8>>(size: usize, signal: I) -> Markers<I::IntoIter> {
+    Markers {
+        detector: MarkerDetector::new(size),
+        signal: signal.into_iter(),
+    }
+}
+
+/// Markers is the iterator returned by [`markers`].
+#[derive(Clone, Debug)]
+pub struct Markers<I> {
+    detector: MarkerDetector,
+    signal: I,
+}
+
+impl<I: Iterator<Item = u8>> Iterator for Markers<I> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.signal
+            .by_ref()
+            .find_map(|byte| self.detector.push(byte))
+    }
+}
+
+/// scan_markers looks for markers of each of the given sizes in a single
+/// pass over the signal, and returns, for each size, the end positions of
+/// every marker found.
+///
+/// Consecutive markers of the same size overlap into a single run of
+/// distinct bytes: only the first marker of each run is reported.
+pub fn scan_markers<I: IntoIterator<Item = u8>>(
+    sizes: &[usize],
+    signal: I,
+) -> BTreeMap<usize, Vec<usize>> {
+    let mut detectors = sizes
+        .iter()
+        .unique()
+        .map(|&size| (MarkerDetector::new(size), None))
+        .collect_vec();
+    let mut found: BTreeMap<usize, Vec<usize>> =
+        sizes.iter().map(|&size| (size, Vec::new())).collect();
+
+    for byte in signal {
+        for (detector, last) in detectors.iter_mut() {
+            let position = detector.push(byte);
+
+            if let Some(position) = position {
+                if *last != Some(position - 1) {
+                    found.entry(detector.size).or_default().push(position);
+                }
+            }
+            *last = position;
+        }
+    }
+
+    found
+}
+
+/// ALPHABET holds the bytes generated signals are made of.
+const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+/// generate_signal builds a random signal of `length` lowercase letters,
+/// whose first marker of `size` distinct letters ends at `first_marker`.
+///
+/// Each letter before the marker is drawn among the ones keeping the window
+/// it ends from holding `size` distinct letters. The marker itself then
+/// starts with the letter preceding it, so that every window overlapping
+/// both holds it twice. The rest of the signal is drawn freely.
+///
+/// # Panics
+///
+/// Panics if `size` is zero or larger than the alphabet, if `first_marker`
+/// is before `size`, or after `length`. Markers of a single letter always
+/// end at the first position.
+pub fn generate_signal<R: Rng + ?Sized>(
+    rng: &mut R,
+    size: usize,
+    first_marker: usize,
+    length: usize,
+) -> Vec<u8> {
+    assert!(
+        (1..=ALPHABET.len()).contains(&size),
+        "marker size should be between 1 and {}",
+        ALPHABET.len()
+    );
+    assert!(
+        size <= first_marker && first_marker <= length,
+        "marker should fit in the signal"
+    );
+    assert!(
+        size > 1 || first_marker == 1,
+        "single letter markers always end at the first position"
+    );
+
+    let prefix = first_marker - size;
+    let mut signal: Vec<u8> = Vec::with_capacity(length);
+
+    for position in 0..prefix {
+        let window = &signal[(position + 1).saturating_sub(size)..];
+        let letter = if window.len() + 1 < size || window.iter().duplicates().next().is_some() {
+            *ALPHABET.choose(rng).unwrap()
+        } else {
+            *window.choose(rng).unwrap()
+        };
+        signal.push(letter);
+    }
+
+    let mut marker = ALPHABET.to_vec();
+    marker.shuffle(rng);
+    marker.truncate(size);
+    if let Some(&last) = signal.last() {
+        let idx = marker.iter().position(|&l| l == last).unwrap_or(0);
+        marker[idx] = marker[0];
+        marker[0] = last;
+    }
+    signal.extend(marker);
+
+    signal.extend((first_marker..length).map(|_| *ALPHABET.choose(rng).unwrap()));
+    signal
+}
+
+/// read_markers returns an iterator over the end positions of every marker
+/// of `size` distinct bytes in the signal read from `reader`, as they are
+/// found. The signal ends at the first line ending, and errors are passed
+/// on to the caller.
+pub fn read_markers<R: BufRead>(size: usize, reader: R) -> ReadMarkers<R> {
+    ReadMarkers {
+        detector: MarkerDetector::new(size),
+        signal: reader.bytes(),
+        done: false,
+    }
+}
+
+/// ReadMarkers is the iterator returned by [`read_markers`].
+#[derive(Debug)]
+pub struct ReadMarkers<R> {
+    detector: MarkerDetector,
+    signal: Bytes<R>,
+
+    /// Whether the signal ended, on a line ending or an error.
+    done: bool,
+}
+
+impl<R: BufRead> Iterator for ReadMarkers<R> {
+    type Item = io::Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        for byte in self.signal.by_ref() {
+            match byte {
+                Ok(b'\n' | b'\r') => break,
+                Ok(byte) => {
+                    if let Some(position) = self.detector.push(byte) {
+                        return Some(Ok(position));
+                    }
+                }
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+
+        self.done = true;
+        None
+    }
+}
+
+impl<R: BufRead> FusedIterator for ReadMarkers<R> {}
+
+/// PACKET_MARKER_SIZE is the number of distinct bytes making up a
+/// start-of-packet marker.
+pub const PACKET_MARKER_SIZE: usize = 4;
+
+/// Packet is a single frame of a datastream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Packet<'a> {
+    /// The offset of the packet's marker in the datastream.
+    pub offset: usize,
+
+    /// The start-of-packet marker.
+    pub marker: &'a [u8],
+
+    /// The payload length announced by the header.
+    pub length: usize,
+
+    /// The payload itself.
+    pub payload: &'a [u8],
+}
+
+/// frames returns an iterator over the packets of a datastream.
+///
+/// The datastream is framed as follows:
+///
+/// * Bytes preceding the first start-of-packet marker, four distinct bytes,
+///   are ignored.
+/// * Each packet starts with a start-of-packet marker, followed by a header
+///   holding the payload length in ASCII decimal digits, terminated by `:`.
+///   The payload follows, and is exactly as long as announced.
+/// * Packets follow each other with nothing in between, until the end of the
+///   datastream: each packet past the first has to start with its marker.
+///
+/// For example, `aaabcd5:hello` holds a single packet, preceded by noise,
+/// whose payload is `hello`.
+///
+/// The iterator stops after the first framing error.
+pub fn frames(stream: &[u8]) -> Frames<'_> {
+    Frames {
+        stream,
+        offset: 0,
+        failed: false,
+    }
+}
+
+/// Frames is the iterator returned by [`frames`].
+#[derive(Clone, Debug)]
+pub struct Frames<'a> {
+    stream: &'a [u8],
+    offset: usize,
+    failed: bool,
+}
+
+impl<'a> Frames<'a> {
+    /// marker locates the marker of the next packet, and returns its offset.
+    fn marker(&self) -> Result<usize, FramingError> {
+        let rest = &self.stream[self.offset..];
+
+        // Only the first packet may be preceded by other bytes.
+        if self.offset == 0 {
+            return markers(PACKET_MARKER_SIZE, rest.iter().copied())
+                .next()
+                .map(|end| end - PACKET_MARKER_SIZE)
+                .ok_or(FramingError::MissingMarker {
+                    offset: self.offset,
+                });
+        }
+
+        if rest.len() < PACKET_MARKER_SIZE {
+            return Err(FramingError::Truncated {
+                offset: self.stream.len(),
+                missing: PACKET_MARKER_SIZE - rest.len(),
+            });
+        }
+
+        match markers(
+            PACKET_MARKER_SIZE,
+            rest[..PACKET_MARKER_SIZE].iter().copied(),
+        )
+        .next()
+        {
+            Some(_) => Ok(self.offset),
+            None => Err(FramingError::InvalidMarker {
+                offset: self.offset,
+            }),
+        }
+    }
+
+    /// header parses the header starting at `offset`, and returns the payload
+    /// length along with the payload offset.
+    fn header(&self, offset: usize) -> Result<(usize, usize), FramingError> {
+        let digits = self.stream[offset..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        let end = offset + digits;
+
+        match self.stream.get(end) {
+            None => Err(FramingError::Truncated {
+                offset: self.stream.len(),
+                missing: 1,
+            }),
+            Some(b':') if digits > 0 => std::str::from_utf8(&self.stream[offset..end])
+                .ok()
+                .and_then(|digits| digits.parse().ok())
+                .map(|length| (length, end + 1))
+                .ok_or(FramingError::InvalidHeader { offset }),
+            Some(_) => Err(FramingError::InvalidHeader { offset: end }),
+        }
+    }
+
+    /// packet parses the next packet, and returns it along with the offset
+    /// it ends at.
+    fn packet(&self) -> Result<(Packet<'a>, usize), FramingError> {
+        let offset = self.marker()?;
+        let (length, start) = self.header(offset + PACKET_MARKER_SIZE)?;
+
+        let available = self.stream.len() - start;
+        if length > available {
+            return Err(FramingError::Truncated {
+                offset: self.stream.len(),
+                missing: length - available,
+            });
+        }
+
+        let packet = Packet {
+            offset,
+            marker: &self.stream[offset..offset + PACKET_MARKER_SIZE],
+            length,
+            payload: &self.stream[start..start + length],
+        };
+
+        Ok((packet, start + length))
+    }
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Result<Packet<'a>, FramingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || (self.offset > 0 && self.offset == self.stream.len()) {
+            return None;
+        }
+
+        match self.packet() {
+            Ok((packet, end)) => {
+                self.offset = end;
+                Some(Ok(packet))
+            }
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// FramingError describes why a datastream cannot be split into packets.
+/// Offsets are counted in bytes from the start of the datastream.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FramingError {
+    /// The datastream holds no start-of-packet marker, from the offset the
+    /// scan for one started at.
+    MissingMarker { offset: usize },
+
+    /// The bytes following a packet don't form a start-of-packet marker.
+    InvalidMarker { offset: usize },
+
+    /// The header holds an unexpected byte, or announces a length that
+    /// doesn't fit in memory.
+    InvalidHeader { offset: usize },
+
+    /// The datastream ends before the end of a packet.
+    Truncated { offset: usize, missing: usize },
+}
+
+impl fmt::Display for FramingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FramingError::MissingMarker { offset } => {
+                write!(f, "offset {offset}: no start-of-packet marker found")
+            }
+            FramingError::InvalidMarker { offset } => {
+                write!(f, "offset {offset}: expected a start-of-packet marker")
+            }
+            FramingError::InvalidHeader { offset } => {
+                write!(f, "offset {offset}: invalid packet header")
+            }
+            FramingError::Truncated { offset, missing } => {
+                write!(f, "offset {offset}: datastream is {missing} byte(s) short")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FramingError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rand::{rngs::StdRng, SeedableRng};
+    use test_case::test_case;
+
+    #[test_case("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19)]
+    #[test_case("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23)]
+    #[test_case("nppdvjthqldpwncqszvftbrmjlhg", 6, 23)]
+    #[test_case("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29)]
+    #[test_case("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26)]
+    fn test_first_marker(signal: &str, packet: usize, message: usize) {
+        assert_eq!(markers(4, signal.bytes()).next(), Some(packet));
+        assert_eq!(markers(14, signal.bytes()).next(), Some(message));
+
+        assert_eq!(
+            read_markers(4, signal.as_bytes()).next().unwrap().unwrap(),
+            packet
+        );
+        assert_eq!(
+            read_markers(14, signal.as_bytes()).next().unwrap().unwrap(),
+            message
+        );
+
+        let input = input_generator_part1(signal);
+        assert_eq!(
+            stream_part1(signal.as_bytes()).unwrap(),
+            solve_part1(&input)
+        );
+        assert_eq!(
+            stream_part2(signal.as_bytes()).unwrap(),
+            solve_part2(&input)
+        );
+    }
+
+    #[test]
+    fn test_stream_stops_at_line_ending() {
+        assert_eq!(part1(&parse("abcabca\n").unwrap()), None);
+        for input in ["abcabca\n", "abcabca\r\n", "abcabca\nd"] {
+            let err = stream_part1(input.as_bytes()).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof, "{input:?}");
+        }
+
+        let mut markers = read_markers(4, &b"abcd\nefgh"[..]);
+        assert_eq!(markers.next().unwrap().unwrap(), 4);
+        assert!(markers.next().is_none());
+        assert!(markers.next().is_none());
+    }
+
+    #[test]
+    fn test_parts() {
+        let datastream = parse("mjqjpqmgbljsphdztnvjfqwrcgsmlb\n").unwrap();
+
+        assert_eq!(part1(&datastream), Some(7));
+        assert_eq!(part2(&datastream), Some(19));
+        assert_eq!(part2(&parse("abcdabcd").unwrap()), None);
+        assert_eq!(
+            parse("abc d").unwrap_err(),
+            ParseError::new(1, "unexpected character ' ' at offset 3")
+        );
+    }
+
+    #[test]
+    fn test_stream_without_marker() {
+        let err = stream_part2("abcdabcd".as_bytes()).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(err.to_string(), "no marker of 14 distinct bytes");
+        assert_eq!(stream_part1("abcdabcd".as_bytes()).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_scan_markers() {
+        let found = scan_markers(&[1, 3, 4, 3, 9], "aabcbcddabcdabcdd".bytes());
+
+        assert_eq!(found.keys().copied().collect_vec(), vec![1, 3, 4, 9]);
+        assert_eq!(found[&1], vec![1]);
+        assert_eq!(found[&3], vec![4, 7, 10]);
+        assert_eq!(found[&4], vec![11]);
+        assert!(found[&9].is_empty());
+    }
+
+    #[test]
+    fn test_all_markers() {
+        assert_eq!(markers(3, "aabcbcdd".bytes()).collect_vec(), vec![4, 7]);
+        assert_eq!(markers(1, "ab".bytes()).collect_vec(), vec![1, 2]);
+        assert_eq!(markers(4, "abc".bytes()).next(), None);
+    }
+
+    #[test]
+    fn test_generate_signal() {
+        let mut rng = StdRng::seed_from_u64(6);
+
+        let signal = generate_signal(&mut rng, 4, 4, 4);
+        assert_eq!(signal.iter().unique().count(), 4);
+
+        let signal = generate_signal(&mut rng, 14, 100, 120);
+        assert_eq!(signal.len(), 120);
+        assert_eq!(markers(14, signal).next(), Some(100));
+    }
+
+    #[test]
+    fn test_frames() {
+        let stream = b"aaabcd5:helloefgh0:wxyz11:hello world";
+        let packets = frames(stream).collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(
+            packets,
+            vec![
+                Packet {
+                    offset: 2,
+                    marker: b"abcd",
+                    length: 5,
+                    payload: b"hello",
+                },
+                Packet {
+                    offset: 13,
+                    marker: b"efgh",
+                    length: 0,
+                    payload: b"",
+                },
+                Packet {
+                    offset: 19,
+                    marker: b"wxyz",
+                    length: 11,
+                    payload: b"hello world",
+                },
+            ]
+        );
+    }
+
+    #[test_case(b"", FramingError::MissingMarker { offset: 0 })]
+    #[test_case(b"aaaabbbb", FramingError::MissingMarker { offset: 0 })]
+    #[test_case(b"abcd5:helloaabb1:x", FramingError::InvalidMarker { offset: 11 })]
+    #[test_case(b"abcdx:", FramingError::InvalidHeader { offset: 4 })]
+    #[test_case(b"abcd:", FramingError::InvalidHeader { offset: 4 })]
+    #[test_case(b"abcd1x:", FramingError::InvalidHeader { offset: 5 })]
+    #[test_case(b"abcd12", FramingError::Truncated { offset: 6, missing: 1 })]
+    #[test_case(b"abcd5:hel", FramingError::Truncated { offset: 9, missing: 2 })]
+    #[test_case(b"abcd5:helloab", FramingError::Truncated { offset: 13, missing: 2 })]
+    fn test_frames_error(stream: &[u8], error: FramingError) {
+        assert_eq!(frames(stream).last(), Some(Err(error)));
+    }
+
+    #[test]
+    fn test_framing_error_display() {
+        assert_eq!(
+            FramingError::MissingMarker { offset: 0 }.to_string(),
+            "offset 0: no start-of-packet marker found"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_markers_match_windows(signal in "[a-f]{0,200}", size in 1usize..8) {
+            let chars = signal.chars().collect_vec();
+            let expected = (size..=chars.len())
+                .filter(|&end| find_marker(&chars[end - size..end], size).is_some())
+                .collect_vec();
+
+            prop_assert_eq!(
+                markers(size, signal.bytes()).next().map(|pos| pos as u64),
+                find_marker(&chars, size)
+            );
+            prop_assert_eq!(markers(size, signal.bytes()).collect_vec(), expected);
+        }
+
+        #[test]
+        fn test_generated_first_marker(
+            seed: u64,
+            size in 2usize..=26,
+            offset in 0usize..300,
+            trailing in 0usize..50,
+        ) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let signal = generate_signal(&mut rng, size, size + offset, size + offset + trailing);
+            let chars = signal.iter().map(|&b| b as char).collect_vec();
+
+            prop_assert_eq!(signal.len(), size + offset + trailing);
+            prop_assert_eq!(markers(size, signal.iter().copied()).next(), Some(size + offset));
+            prop_assert_eq!(find_marker(&chars, size), Some((size + offset) as u64));
+        }
+    }
 }
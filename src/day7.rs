@@ -1,67 +1,62 @@
+use crate::{
+    error::ParseError,
+    solution::{self, Solution},
+};
 use camino::Utf8PathBuf;
-use id_tree::{InsertBehavior, Node, Tree};
+use id_tree::{InsertBehavior, Node, NodeIdError, Tree};
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while1},
     character::complete::{digit1, space1},
     combinator::{all_consuming, map, map_res, value},
     error::context,
-    multi::separated_list1,
     sequence::{pair, preceded, separated_pair, terminated},
     IResult,
 };
 
 #[aoc_generator(day7)]
-pub fn input_generator_part1(input: &str) -> Vec<LogLine> {
-    parse_input(input).unwrap().1
+pub fn input_generator_part1(input: &str) -> Result<Vec<LogLine>, ParseError> {
+    parse_log(input)
 }
 
 #[aoc(day7, part1)]
 pub fn solve_part1(input: &[LogLine]) -> color_eyre::Result<usize> {
-    let fs = execute_logs(input.to_vec())?;
-
-    // fs is a tree and can be printed using the `write_formatted` method.
-    let mut fs_representation = String::new();
-    fs.write_formatted(&mut fs_representation)?;
-    println!("{fs_representation}");
-
-    let total = fs
-        .traverse_pre_order(fs.root_node_id().unwrap())?
-        .filter(|node| !node.children().is_empty())
-        .map(|node| fs_size_at_node(&fs, node).unwrap())
-        .filter(|&size| size <= 100_000)
-        .inspect(|size| {
-            dbg!(size);
-        })
-        .sum::<usize>();
+    let fs = Filesystem {
+        tree: execute_logs(input.to_vec())?,
+    };
 
-    Ok(total)
+    Ok(part1(&fs))
 }
 
 #[aoc(day7, part2)]
 pub fn solve_part2(input: &[LogLine]) -> color_eyre::Result<usize> {
-    let fs = execute_logs(input.to_vec())?;
-
-    // fs is a tree and can be printed using the `write_formatted` method.
-    let mut fs_representation = String::new();
-    fs.write_formatted(&mut fs_representation)?;
-    println!("{fs_representation}");
-
-    let used = fs_size_at_node(&fs, fs.get(fs.root_node_id().unwrap())?)?;
-    let free = 70_000_000_usize - used;
-
-    let smallest = fs
-        .traverse_pre_order(fs.root_node_id().unwrap())?
-        .filter(|node| !node.children().is_empty())
-        .map(|node| fs_size_at_node(&fs, node).unwrap())
-        .filter(|&size| size >= 30_000_000 - free)
-        .inspect(|size| {
-            dbg!(size);
-        })
-        .min()
-        .unwrap();
+    let fs = Filesystem {
+        tree: execute_logs(input.to_vec())?,
+    };
 
-    Ok(smallest)
+    Ok(part2(&fs))
+}
+
+/// Filesystem is the directory tree rebuilt from the terminal output.
+/// Directories are the nodes with children, files are the leaves.
+#[derive(Clone, Debug)]
+pub struct Filesystem {
+    pub tree: Tree<FsNode>,
+}
+
+impl Filesystem {
+    /// directory_sizes returns the total size of each directory holding
+    /// anything, in pre-order from the root.
+    pub fn directory_sizes(&self) -> Vec<usize> {
+        let root = self.tree.root_node_id().expect("the filesystem has a root");
+
+        self.tree
+            .traverse_pre_order(root)
+            .unwrap()
+            .filter(|node| !node.children().is_empty())
+            .map(|node| fs_size_at_node(&self.tree, node).unwrap())
+            .collect()
+    }
 }
 
 fn fs_size_at_node(fs: &Tree<FsNode>, node: &Node<FsNode>) -> color_eyre::Result<usize> {
@@ -73,27 +68,106 @@ fn fs_size_at_node(fs: &Tree<FsNode>, node: &Node<FsNode>) -> color_eyre::Result
     Ok(total)
 }
 
-fn execute_logs(input: Vec<LogLine>) -> color_eyre::Result<Tree<FsNode>> {
+/// parse parses the terminal output, one command or directory entry per
+/// line, and rebuilds the filesystem it explores.
+pub fn parse(input: &str) -> Result<Filesystem, ParseError> {
+    Ok(Filesystem {
+        tree: execute_logs(parse_log(input)?)?,
+    })
+}
+
+/// parse_log parses the terminal output into its lines.
+fn parse_log(input: &str) -> Result<Vec<LogLine>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            all_consuming(parse_log_line)(line)
+                .map(|(_, line)| line)
+                .map_err(|_| ParseError::new(idx + 1, format!("invalid log line {line:?}")))
+        })
+        .collect()
+}
+
+/// part1 returns the sum of the sizes of the directories of at most 100000.
+pub fn part1(fs: &Filesystem) -> usize {
+    fs.directory_sizes()
+        .into_iter()
+        .filter(|&size| size <= 100_000)
+        .sum()
+}
+
+/// part2 returns the size of the smallest directory to delete to free up
+/// enough space for the update.
+pub fn part2(fs: &Filesystem) -> usize {
+    let sizes = fs.directory_sizes();
+    let used = sizes.first().copied().unwrap_or(0);
+    let free = 70_000_000_usize.saturating_sub(used);
+    let needed = 30_000_000_usize.saturating_sub(free);
+
+    // The root directory always frees up enough space.
+    sizes
+        .into_iter()
+        .filter(|&size| size >= needed)
+        .min()
+        .unwrap_or(0)
+}
+
+/// Day7 is the [`Solution`] to the no space left on device puzzle.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Input = Filesystem;
+    type Output = usize;
+
+    fn parse(&self, input: &str) -> Result<Filesystem, solution::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Filesystem) -> Result<usize, solution::Error> {
+        Ok(part1(input))
+    }
+
+    fn part2(&self, input: &Filesystem) -> Result<usize, solution::Error> {
+        Ok(part2(input))
+    }
+}
+
+/// execute_logs replays the terminal output, and returns the filesystem tree
+/// it explores. Lines are numbered from 1, in the order of `input`.
+fn execute_logs(input: Vec<LogLine>) -> Result<Tree<FsNode>, ParseError> {
     let mut tree = Tree::<FsNode>::new();
 
-    let root = tree.insert(
-        Node::new(FsNode {
-            path: "/".into(),
-            size: 0,
-        }),
-        InsertBehavior::AsRoot,
-    )?;
+    let root = tree
+        .insert(
+            Node::new(FsNode {
+                path: "/".into(),
+                size: 0,
+            }),
+            InsertBehavior::AsRoot,
+        )
+        .expect("an empty tree accepts a root");
 
     let mut current = root;
 
-    for line in input {
+    for (idx, line) in input.into_iter().enumerate() {
+        let at_line = |err: NodeIdError| ParseError::new(idx + 1, err.to_string());
+
         match line {
             LogLine::Command(c) => match c {
                 Command::Ls => {}
                 Command::Cd(dir) => match dir.as_str() {
                     "/" => {}
                     ".." => {
-                        current = tree.get(&current)?.parent().unwrap().clone();
+                        current = tree
+                            .get(&current)
+                            .map_err(at_line)?
+                            .parent()
+                            .ok_or_else(|| {
+                                ParseError::new(idx + 1, "cd .. from the root directory")
+                            })?
+                            .clone();
                     }
                     _ => {
                         let node = FsNode {
@@ -101,20 +175,22 @@ fn execute_logs(input: Vec<LogLine>) -> color_eyre::Result<Tree<FsNode>> {
                             size: 0,
                         };
 
-                        current =
-                            tree.insert(Node::new(node), InsertBehavior::UnderNode(&current))?;
+                        current = tree
+                            .insert(Node::new(node), InsertBehavior::UnderNode(&current))
+                            .map_err(at_line)?;
                     }
                 },
             },
             LogLine::Entry(entry) => match entry {
-                Entry::Directory(dir) => {}
+                Entry::Directory(_) => {}
                 Entry::File(size, path) => {
                     let node = FsNode {
                         path: path.clone(),
                         size,
                     };
 
-                    tree.insert(Node::new(node), InsertBehavior::UnderNode(&current))?;
+                    tree.insert(Node::new(node), InsertBehavior::UnderNode(&current))
+                        .map_err(at_line)?;
                 }
             },
         }
@@ -123,14 +199,15 @@ fn execute_logs(input: Vec<LogLine>) -> color_eyre::Result<Tree<FsNode>> {
     Ok(tree)
 }
 
-#[derive(Clone, Debug, Default)]
+/// FsNode is a file or a directory of the filesystem. Directories have no
+/// size of their own.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct FsNode {
-    path: Utf8PathBuf,
-    size: usize,
-}
+    /// The name of the file or directory, relative to its parent.
+    pub path: Utf8PathBuf,
 
-fn parse_input(input: &str) -> IResult<&str, Vec<LogLine>> {
-    all_consuming(separated_list1(tag("\n"), parse_log_line))(input)
+    /// The size of the file.
+    pub size: usize,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -215,3 +292,57 @@ fn unsigned_size(input: &str) -> IResult<&str, usize> {
         map_res(digit1, |s: &str| s.parse::<usize>()),
     )(input)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k";
+
+    #[test]
+    fn test_parts() {
+        let fs = parse(EXAMPLE).unwrap();
+
+        assert_eq!(fs.directory_sizes(), vec![48381165, 94853, 584, 24933642]);
+        assert_eq!(part1(&fs), 95437);
+        assert_eq!(part2(&fs), 24933642);
+
+        let log = input_generator_part1(EXAMPLE).unwrap();
+        assert_eq!(solve_part1(&log).unwrap(), part1(&fs));
+        assert_eq!(solve_part2(&log).unwrap(), part2(&fs));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("$ cd /\n$ cd ..").unwrap_err(),
+            ParseError::new(2, "cd .. from the root directory")
+        );
+        assert_eq!(
+            parse("$ cd /\n$ rm -rf a").unwrap_err(),
+            ParseError::new(2, "invalid log line \"$ rm -rf a\"")
+        );
+    }
+}
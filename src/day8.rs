@@ -2,23 +2,73 @@ use std::ops::{Add, Not};
 
 use itertools::Itertools;
 
+use crate::{
+    error::ParseError,
+    solution::{self, Solution},
+};
+
 #[aoc_generator(day8)]
-fn input_generator_part2(input: &str) -> Forest {
-    let mut forest = Forest::new(input.lines().count(), input.lines().next().unwrap().len());
+pub fn input_generator_part2(input: &str) -> Result<Forest, ParseError> {
+    parse(input)
+}
+
+/// parse parses the height map of the forest, one row of digits per line.
+/// Every row must be as wide as the first one.
+pub fn parse(input: &str) -> Result<Forest, ParseError> {
+    let width = input.lines().next().map_or(0, str::len);
+    let mut forest = Forest::new(input.lines().count(), width);
+
+    for (y, row) in input.lines().enumerate() {
+        if row.len() != width || !row.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseError::new(
+                y + 1,
+                format!("invalid row {row:?}, expected {width} digits"),
+            ));
+        }
 
-    input.lines().enumerate().for_each(|(y, row)| {
         row.chars().enumerate().for_each(|(x, column)| {
             let ix = x as isize;
             let iy = y as isize;
             forest.set_tree(Vector2 { x: ix, y: iy }, column as usize - '0' as usize)
-        })
-    });
+        });
+    }
+
+    Ok(forest)
+}
 
-    forest
+/// part1 returns the number of trees visible from outside the forest.
+pub fn part1(forest: &Forest) -> usize {
+    solve_part1(forest)
+}
+
+/// part2 returns the highest scenic score of any tree.
+pub fn part2(forest: &Forest) -> usize {
+    solve_part2(forest)
+}
+
+/// Day8 is the [`Solution`] to the treetop tree house puzzle.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input = Forest;
+    type Output = usize;
+
+    fn parse(&self, input: &str) -> Result<Forest, solution::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Forest) -> Result<usize, solution::Error> {
+        Ok(part1(input))
+    }
+
+    fn part2(&self, input: &Forest) -> Result<usize, solution::Error> {
+        Ok(part2(input))
+    }
 }
 
 #[aoc(day8, part1)]
-fn solve_part1(input: &Forest) -> usize {
+pub fn solve_part1(input: &Forest) -> usize {
     // We iterate through each tree one by one
     (0..input.width)
         .cartesian_product(0..input.height)
@@ -32,7 +82,7 @@ fn solve_part1(input: &Forest) -> usize {
 }
 
 #[aoc(day8, part2)]
-fn solve_part2(input: &Forest) -> usize {
+pub fn solve_part2(input: &Forest) -> usize {
     (0..input.width)
         .cartesian_product(0..input.height)
         .map(|(x, y)| {
@@ -41,13 +91,20 @@ fn solve_part2(input: &Forest) -> usize {
             input.viewing_distance(Vector2 { x: x_pos, y: y_pos })
         })
         .max()
-        .unwrap()
+        .unwrap_or(0)
 }
 
-struct Forest {
-    height: usize,
-    width: usize,
-    trees: Vec<usize>,
+/// Forest is a grid of trees, each with a height from 0 to 9.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Forest {
+    /// The number of rows.
+    pub height: usize,
+
+    /// The number of columns.
+    pub width: usize,
+
+    /// The height of each tree, row after row from the top one.
+    pub trees: Vec<usize>,
 }
 
 impl Forest {
@@ -209,3 +266,32 @@ impl Add for Vector2 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "30373\n25512\n65332\n33549\n35390";
+
+    #[test]
+    fn test_parts() {
+        let forest = parse(EXAMPLE).unwrap();
+
+        assert_eq!((forest.width, forest.height), (5, 5));
+        assert_eq!(forest.trees[5..10], [2, 5, 5, 1, 2]);
+        assert_eq!(part1(&forest), 21);
+        assert_eq!(part2(&forest), 8);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("303\n25\n653").unwrap_err(),
+            ParseError::new(2, "invalid row \"25\", expected 3 digits")
+        );
+        assert_eq!(
+            parse("303\n2x5").unwrap_err(),
+            ParseError::new(2, "invalid row \"2x5\", expected 3 digits")
+        );
+    }
+}
@@ -0,0 +1,29 @@
+use std::fmt;
+
+/// ParseError describes why a puzzle input could not be parsed. Lines are
+/// numbered from 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line the error was found on.
+    pub line: usize,
+
+    /// What is wrong with the line.
+    pub reason: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, reason: impl Into<String>) -> Self {
+        ParseError {
+            line,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -3,6 +3,7 @@ extern crate aoc_runner;
 #[macro_use]
 extern crate aoc_runner_derive;
 
+pub mod answers;
 pub mod day1;
 pub mod day2;
 pub mod day3;
@@ -11,5 +12,7 @@ pub mod day5;
 pub mod day6;
 pub mod day7;
 pub mod day8;
+pub mod error;
+pub mod solution;
 
 aoc_lib! { year = 2022 }
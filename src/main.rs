@@ -1,7 +1,413 @@
-extern crate aoc_runner;
-extern crate aoc_runner_derive;
-extern crate twentytwentytwo;
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::{Parser, Subcommand};
+use color_eyre::eyre::{bail, eyre, WrapErr};
+use itertools::Itertools;
+use std::{
+    fs,
+    io::{self, Read},
+    time::Duration,
+};
+use twentytwentytwo::{
+    answers::AnswerSheet,
+    solution::{registry, Registry, Run},
+};
 
-use aoc_runner_derive::aoc_main;
+/// Solutions to the Advent of Code puzzles, run from the repository's root.
+///
+/// Puzzle inputs are read from `input/<year>/day<day>.txt`, and their
+/// expected answers from `answers/<year>.toml`.
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Cli {
+    /// The year of the puzzles.
+    #[arg(long, global = true, default_value_t = 2022)]
+    year: u16,
 
-aoc_main! { lib = twentytwentytwo }
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Solve a puzzle.
+    Run {
+        /// The day of the puzzle.
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+
+        /// Solve a single part, rather than both.
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+
+        /// Read the puzzle input from this file, or `-` for the standard
+        /// input, rather than from the input directory.
+        #[arg(long)]
+        input: Option<Utf8PathBuf>,
+    },
+
+    /// Time the parsing and both parts of a puzzle over several runs.
+    Bench {
+        /// The day of the puzzle.
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+
+        /// The number of runs.
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+        iterations: u32,
+
+        /// Read the puzzle input from this file, or `-` for the standard
+        /// input, rather than from the input directory.
+        #[arg(long)]
+        input: Option<Utf8PathBuf>,
+    },
+
+    /// Solve a puzzle against its example input, from `tests/examples/`.
+    Test {
+        /// The day of the puzzle.
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+
+        /// Solve a single part, rather than both.
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+    },
+
+    /// Solve every implemented puzzle of the year against its input, and
+    /// fail if any answer differs from the recorded one. Parts not solved
+    /// yet are skipped.
+    Check,
+
+    /// Solve implemented puzzles of the year against their input, and record
+    /// the answers to the parts solved so far as the expected ones.
+    Record {
+        /// Only record the answers to the puzzle of this day.
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: Option<u8>,
+    },
+
+    /// List the implemented puzzles of the year, the parts solved so far,
+    /// and whether their input is available.
+    List,
+
+    /// Scaffold the module of a new puzzle, and register it.
+    New {
+        /// The day of the puzzle.
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+    },
+}
+
+fn main() -> color_eyre::Result<()> {
+    color_eyre::install()?;
+
+    let cli = Cli::parse();
+    let registry = registry();
+
+    match cli.command {
+        Command::Run { day, part, input } => {
+            let input = read_input(cli.year, day, input.as_deref())?;
+            let parts = part.map_or(vec![1, 2], |part| vec![part]);
+            let run = solve(&registry, cli.year, day, &input, &parts)?;
+
+            for answer in run.parts {
+                println!("day {day} part {}: {}", answer.part, answer.value);
+            }
+        }
+        Command::Bench {
+            day,
+            iterations,
+            input,
+        } => {
+            let input = read_input(cli.year, day, input.as_deref())?;
+            let runs = (0..iterations)
+                .map(|_| solve(&registry, cli.year, day, &input, &[1, 2]))
+                .collect::<color_eyre::Result<Vec<_>>>()?;
+
+            println!("day {day}, {iterations} runs");
+            print_timings("parse", runs.iter().map(|run| run.parse));
+            for (idx, answer) in runs[0].parts.iter().enumerate() {
+                print_timings(
+                    &format!("part {}", answer.part),
+                    runs.iter().map(|run| run.parts[idx].elapsed),
+                );
+            }
+        }
+        Command::Test { day, part } => {
+            if cli.year != 2022 {
+                bail!("only puzzles of 2022 have example inputs");
+            }
+
+            let path = example_path(day);
+            let input = read_input(cli.year, day, Some(&path))?;
+            let parts = part.map_or(vec![1, 2], |part| vec![part]);
+            let run = solve(&registry, cli.year, day, &input, &parts)?;
+
+            for answer in run.parts {
+                println!("day {day} part {} (example): {}", answer.part, answer.value);
+            }
+        }
+        Command::Check => {
+            let sheet = read_answers(cli.year)?;
+            let mut failures = 0;
+
+            for (year, day) in registry.puzzles().filter(|&(year, _)| year == cli.year) {
+                let parts = registry.get(year, day).unwrap().parts();
+                let run = match read_input(year, day, None)
+                    .and_then(|input| solve(&registry, year, day, &input, parts))
+                {
+                    Ok(run) => run,
+                    Err(err) => {
+                        failures += 1;
+                        println!("day {day}: FAILED: {err:#}");
+                        continue;
+                    }
+                };
+
+                for answer in run.parts {
+                    let part = answer.part;
+                    match sheet.get(day, part) {
+                        Some(expected) if expected == answer.value => {
+                            println!("day {day} part {part}: ok ({expected})")
+                        }
+                        Some(expected) => {
+                            failures += 1;
+                            println!(
+                                "day {day} part {part}: MISMATCH: expected {expected}, got {}",
+                                answer.value
+                            );
+                        }
+                        None => {
+                            failures += 1;
+                            println!("day {day} part {part}: NOT RECORDED: got {}", answer.value);
+                        }
+                    }
+                }
+            }
+
+            if failures > 0 {
+                bail!("{failures} failure(s) against {}", answers_path(cli.year));
+            }
+        }
+        Command::Record { day } => {
+            let path = answers_path(cli.year);
+            let mut sheet = if path.exists() {
+                read_answers(cli.year)?
+            } else {
+                AnswerSheet::new()
+            };
+
+            let puzzles = registry
+                .puzzles()
+                .filter(|&(year, other)| year == cli.year && day.is_none_or(|day| day == other))
+                .collect::<Vec<_>>();
+            if puzzles.is_empty() {
+                bail!("no implemented puzzle to record");
+            }
+
+            for (year, day) in puzzles {
+                let input = read_input(year, day, None)?;
+                let parts = registry.get(year, day).unwrap().parts();
+                for answer in solve(&registry, year, day, &input, parts)?.parts {
+                    match sheet.set(day, answer.part, answer.value.clone()) {
+                        Some(previous) if previous != answer.value => println!(
+                            "day {day} part {}: {previous} -> {}",
+                            answer.part, answer.value
+                        ),
+                        _ => println!("day {day} part {}: {}", answer.part, answer.value),
+                    }
+                }
+            }
+
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).wrap_err_with(|| format!("creating {dir}"))?;
+            }
+            fs::write(&path, sheet.to_string()).wrap_err_with(|| format!("writing {path}"))?;
+            println!("recorded {path}");
+        }
+        Command::List => {
+            for (year, day) in registry.puzzles().filter(|&(year, _)| year == cli.year) {
+                let parts = match registry.get(year, day).unwrap().parts() {
+                    [] => "no part solved".to_string(),
+                    [part] => format!("part {part}"),
+                    parts => format!("parts {}", parts.iter().join(", ")),
+                };
+                let path = input_path(year, day);
+                let status = if path.exists() { "present" } else { "missing" };
+                println!("day {day:>2}: {parts}, input {path} {status}");
+            }
+        }
+        Command::New { day } => scaffold(cli.year, day)?,
+    }
+
+    Ok(())
+}
+
+fn input_path(year: u16, day: u8) -> Utf8PathBuf {
+    Utf8PathBuf::from(format!("input/{year}/day{day}.txt"))
+}
+
+fn example_path(day: u8) -> Utf8PathBuf {
+    Utf8PathBuf::from(format!("tests/examples/day{day}.txt"))
+}
+
+fn answers_path(year: u16) -> Utf8PathBuf {
+    Utf8PathBuf::from(format!("answers/{year}.toml"))
+}
+
+/// read_answers reads the answer sheet of the year.
+fn read_answers(year: u16) -> color_eyre::Result<AnswerSheet> {
+    let path = answers_path(year);
+    let sheet = fs::read_to_string(&path).wrap_err_with(|| {
+        format!("reading {path}, answers can be recorded with the `record` command")
+    })?;
+
+    AnswerSheet::parse(&sheet).wrap_err_with(|| format!("parsing {path}"))
+}
+
+/// read_input reads the puzzle input from `path`, `-` being the standard
+/// input, or from the input directory when no path is given.
+fn read_input(year: u16, day: u8, path: Option<&Utf8Path>) -> color_eyre::Result<String> {
+    let path = path.map_or_else(|| input_path(year, day), Utf8Path::to_path_buf);
+
+    if path == "-" {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .wrap_err("reading the standard input")?;
+        return Ok(input);
+    }
+
+    fs::read_to_string(&path).wrap_err_with(|| format!("reading {path}"))
+}
+
+fn solve(
+    registry: &Registry,
+    year: u16,
+    day: u8,
+    input: &str,
+    parts: &[u8],
+) -> color_eyre::Result<Run> {
+    let solver = registry
+        .get(year, day)
+        .ok_or_else(|| eyre!("day {day} of {year} is not implemented"))?;
+
+    solver
+        .run(input, parts)
+        .map_err(|err| eyre!(err).wrap_err(format!("solving day {day} of {year}")))
+}
+
+fn print_timings(phase: &str, timings: impl Iterator<Item = Duration>) {
+    let timings = timings.collect::<Vec<_>>();
+    let min = timings.iter().min().copied().unwrap_or_default();
+    let max = timings.iter().max().copied().unwrap_or_default();
+    let mean = timings.iter().sum::<Duration>() / timings.len().max(1) as u32;
+
+    println!("  {phase:<7} min {min:>12.3?}  mean {mean:>12.3?}  max {max:>12.3?}");
+}
+
+/// scaffold creates the module of a new puzzle from a template, along with
+/// an empty input file, and registers the module in the library.
+fn scaffold(year: u16, day: u8) -> color_eyre::Result<()> {
+    if year != 2022 {
+        bail!("only puzzles of 2022 can be scaffolded");
+    }
+
+    let module = Utf8PathBuf::from(format!("src/day{day}.rs"));
+    if module.exists() {
+        bail!("{module} already exists");
+    }
+
+    let lib = fs::read_to_string("src/lib.rs").wrap_err("reading src/lib.rs")?;
+    let solution = fs::read_to_string("src/solution.rs").wrap_err("reading src/solution.rs")?;
+
+    let lib = insert_after_last(&lib, "pub mod day", &format!("pub mod day{day};\n"))
+        .ok_or_else(|| eyre!("no day module declared in src/lib.rs"))?;
+    let solution = insert_after_last(
+        &solution,
+        "    registry.register(",
+        &format!("    registry.register(2022, {day}, crate::day{day}::Day{day});\n"),
+    )
+    .ok_or_else(|| eyre!("no solver registered in src/solution.rs"))?;
+
+    fs::write(&module, TEMPLATE.replace("{day}", &day.to_string()))
+        .wrap_err_with(|| format!("writing {module}"))?;
+    fs::write("src/lib.rs", lib).wrap_err("writing src/lib.rs")?;
+    fs::write("src/solution.rs", solution).wrap_err("writing src/solution.rs")?;
+
+    let input = input_path(year, day);
+    if !input.exists() {
+        fs::write(&input, "").wrap_err_with(|| format!("writing {input}"))?;
+    }
+
+    println!("created {module} and {input}");
+    Ok(())
+}
+
+/// insert_after_last inserts `line` after the last line of `source` starting
+/// with `prefix`.
+fn insert_after_last(source: &str, prefix: &str, line: &str) -> Option<String> {
+    let start = source
+        .rmatch_indices('\n')
+        .map(|(idx, _)| idx + 1)
+        .find(|&idx| source[idx..].starts_with(prefix))?;
+    let end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |idx| start + idx + 1);
+
+    Some(format!("{}{line}{}", &source[..end], &source[end..]))
+}
+
+const TEMPLATE: &str = r#"use crate::{
+    error::ParseError,
+    solution::{self, Solution},
+};
+
+/// Puzzle is the parsed puzzle input.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Puzzle {
+    pub lines: Vec<String>,
+}
+
+/// parse parses the puzzle input.
+pub fn parse(input: &str) -> Result<Puzzle, ParseError> {
+    Ok(Puzzle {
+        lines: input.lines().map(String::from).collect(),
+    })
+}
+
+/// part1 solves the first part of the puzzle.
+pub fn part1(_puzzle: &Puzzle) -> Option<u64> {
+    None
+}
+
+/// part2 solves the second part of the puzzle.
+pub fn part2(_puzzle: &Puzzle) -> Option<u64> {
+    None
+}
+
+/// Day{day} is the [`Solution`] to the puzzle of day {day}.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Day{day};
+
+impl Solution for Day{day} {
+    type Input = Puzzle;
+    type Output = u64;
+
+    fn parse(&self, input: &str) -> Result<Puzzle, solution::Error> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, input: &Puzzle) -> Result<u64, solution::Error> {
+        part1(input).ok_or_else(|| "part 1 is not solved yet".into())
+    }
+
+    fn part2(&self, input: &Puzzle) -> Result<u64, solution::Error> {
+        part2(input).ok_or_else(|| "part 2 is not solved yet".into())
+    }
+
+    // List the parts here as they get solved.
+    fn parts(&self) -> &'static [u8] {
+        &[]
+    }
+}
+"#;
@@ -0,0 +1,279 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// Error is any error raised while solving a puzzle.
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Solution solves both parts of a puzzle, from its parsed input.
+pub trait Solution {
+    /// The parsed puzzle input, shared by both parts.
+    type Input;
+
+    /// The answer to either part.
+    type Output: fmt::Display;
+
+    /// parse parses the puzzle input.
+    fn parse(&self, input: &str) -> Result<Self::Input, Error>;
+
+    /// part1 solves the first part of the puzzle.
+    fn part1(&self, input: &Self::Input) -> Result<Self::Output, Error>;
+
+    /// part2 solves the second part of the puzzle.
+    fn part2(&self, input: &Self::Input) -> Result<Self::Output, Error>;
+
+    /// parts returns the parts solved so far, in order: both of them unless
+    /// overridden.
+    fn parts(&self) -> &'static [u8] {
+        &[1, 2]
+    }
+}
+
+/// Answers holds the rendered answers to both parts of a puzzle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Answers {
+    pub part1: String,
+    pub part2: String,
+}
+
+/// Run holds the answers to some parts of a puzzle, along with how long each
+/// phase took.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Run {
+    /// The time spent parsing the input.
+    pub parse: Duration,
+
+    /// The answers to the parts solved, in the order they were requested.
+    pub parts: Vec<Answer>,
+}
+
+/// Answer is the rendered answer to a single part of a puzzle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Answer {
+    /// The part, 1 or 2.
+    pub part: u8,
+
+    /// The answer, as displayed.
+    pub value: String,
+
+    /// The time spent solving the part, parsing excluded.
+    pub elapsed: Duration,
+}
+
+/// Solver is a [`Solution`] with its types erased, so that solutions to
+/// different puzzles can be held together in a [`Registry`].
+pub trait Solver {
+    /// run parses the puzzle input once, then solves the given parts in
+    /// turn, timing each phase. Parts other than 1 and 2 are an error.
+    fn run(&self, input: &str, parts: &[u8]) -> Result<Run, Error>;
+
+    /// parts returns the parts of the puzzle solved so far, in order.
+    fn parts(&self) -> &'static [u8];
+
+    /// solve parses the puzzle input and solves both parts.
+    fn solve(&self, input: &str) -> Result<Answers, Error> {
+        let mut run = self.run(input, &[1, 2])?;
+        let part2 = run.parts.pop().unwrap().value;
+        let part1 = run.parts.pop().unwrap().value;
+
+        Ok(Answers { part1, part2 })
+    }
+}
+
+impl<S: Solution> Solver for S {
+    fn run(&self, input: &str, parts: &[u8]) -> Result<Run, Error> {
+        if let Some(part) = parts.iter().find(|&&part| part != 1 && part != 2) {
+            return Err(format!("no such part {part}").into());
+        }
+
+        let start = Instant::now();
+        let input = self.parse(input)?;
+        let parse = start.elapsed();
+
+        let parts = parts
+            .iter()
+            .map(|&part| {
+                let start = Instant::now();
+                let value = match part {
+                    1 => self.part1(&input)?,
+                    _ => self.part2(&input)?,
+                };
+
+                Ok(Answer {
+                    part,
+                    value: value.to_string(),
+                    elapsed: start.elapsed(),
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Run { parse, parts })
+    }
+
+    fn parts(&self) -> &'static [u8] {
+        Solution::parts(self)
+    }
+}
+
+/// Registry maps puzzles, identified by their year and day, to their solver.
+#[derive(Default)]
+pub struct Registry {
+    solvers: BTreeMap<(u16, u8), Box<dyn Solver>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// register adds the solver of the puzzle of the given year and day,
+    /// replacing the previous one, if any.
+    pub fn register(&mut self, year: u16, day: u8, solver: impl Solver + 'static) {
+        self.solvers.insert((year, day), Box::new(solver));
+    }
+
+    /// get returns the solver of the puzzle of the given year and day.
+    pub fn get(&self, year: u16, day: u8) -> Option<&dyn Solver> {
+        self.solvers.get(&(year, day)).map(Box::as_ref)
+    }
+
+    /// puzzles returns the year and day of every registered puzzle, in
+    /// chronological order.
+    pub fn puzzles(&self) -> impl Iterator<Item = (u16, u8)> + '_ {
+        self.solvers.keys().copied()
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.puzzles()).finish()
+    }
+}
+
+/// registry returns a registry holding the solver of every puzzle solved in
+/// this crate.
+pub fn registry() -> Registry {
+    let mut registry = Registry::new();
+    registry.register(2022, 1, crate::day1::Day1);
+    registry.register(2022, 2, crate::day2::Day2);
+    registry.register(2022, 3, crate::day3::Day3);
+    registry.register(2022, 4, crate::day4::Day4);
+    registry.register(2022, 5, crate::day5::Day5);
+    registry.register(2022, 6, crate::day6::Day6);
+    registry.register(2022, 7, crate::day7::Day7);
+    registry.register(2022, 8, crate::day8::Day8);
+    registry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        let registry = registry();
+
+        // Days get registered in order, without gaps, as they are solved.
+        let puzzles = registry.puzzles().collect::<Vec<_>>();
+        let (_, last) = *puzzles.last().unwrap();
+        assert!(last >= 8);
+        assert_eq!(
+            puzzles,
+            (1..=last).map(|day| (2022, day)).collect::<Vec<_>>()
+        );
+        assert_eq!(registry.get(2022, 8).unwrap().parts(), &[1, 2]);
+        assert!(registry.get(2022, 25).is_none());
+        assert!(registry.get(2021, 1).is_none());
+
+        let solver = registry.get(2022, 2).unwrap();
+        assert_eq!(
+            solver.solve("A Y\nB X\nC Z").unwrap(),
+            Answers {
+                part1: "15".to_string(),
+                part2: "12".to_string(),
+            }
+        );
+        assert_eq!(
+            registry
+                .get(2022, 6)
+                .unwrap()
+                .solve("mjqjpqmgbljsphdztnvjfqwrcgsmlb")
+                .unwrap(),
+            Answers {
+                part1: "7".to_string(),
+                part2: "19".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_run() {
+        let run = registry()
+            .get(2022, 4)
+            .unwrap()
+            .run("2-4,6-8\n2-8,3-7\n6-6,4-6", &[2])
+            .unwrap();
+
+        assert_eq!(run.parts.len(), 1);
+        assert_eq!(run.parts[0].part, 2);
+        assert_eq!(run.parts[0].value, "2");
+
+        let err = registry()
+            .get(2022, 4)
+            .unwrap()
+            .run("", &[1, 3])
+            .unwrap_err();
+        assert_eq!(err.to_string(), "no such part 3");
+    }
+
+    #[test]
+    fn test_solver_errors() {
+        let registry = registry();
+
+        let err = registry.get(2022, 1).unwrap().solve("1\nx").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: \"x\" is not a number of calories");
+
+        let err = registry
+            .get(2022, 6)
+            .unwrap()
+            .solve("abcdabcd")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "no start-of-message marker");
+    }
+
+    #[test]
+    fn test_register() {
+        struct Constant;
+
+        impl Solution for Constant {
+            type Input = usize;
+            type Output = usize;
+
+            fn parse(&self, input: &str) -> Result<usize, Error> {
+                Ok(input.len())
+            }
+
+            fn part1(&self, input: &usize) -> Result<usize, Error> {
+                Ok(*input)
+            }
+
+            fn part2(&self, input: &usize) -> Result<usize, Error> {
+                Ok(input * 2)
+            }
+        }
+
+        let mut registry = Registry::new();
+        registry.register(2022, 1, Constant);
+
+        assert_eq!(format!("{registry:?}"), "[(2022, 1)]");
+        assert_eq!(
+            registry.get(2022, 1).unwrap().solve("abc").unwrap(),
+            Answers {
+                part1: "3".to_string(),
+                part2: "6".to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! Solves the example of each day's puzzle, through the runner's generators
+//! and solvers, the library API, the streaming solvers and the registry.
+//!
+//! The runner hands the generators the input with its trailing newline
+//! trimmed, while the input files end with one: every example is solved both
+//! ways.
+
+use std::fs;
+use test_case::test_case;
+use twentytwentytwo::{day1, day2, day3, day4, day5, day6, day7, day8, solution::registry};
+
+/// example returns the example input of the day, ending with `ending`
+/// rather than a newline.
+fn example(day: u8, ending: &str) -> String {
+    let path = format!("{}/tests/examples/day{day}.txt", env!("CARGO_MANIFEST_DIR"));
+    let input = fs::read_to_string(&path).unwrap_or_else(|err| panic!("{path}: {err}"));
+
+    input.trim_end_matches('\n').to_string() + ending
+}
+
+#[test_case(""; "trimmed")]
+#[test_case("\n"; "trailing newline")]
+fn test_day1(ending: &str) {
+    let input = example(1, ending);
+
+    let payloads = day1::input_generator(&input).unwrap();
+    assert_eq!(day1::solve_part1(&payloads), 24000);
+    assert_eq!(day1::solve_part2(&payloads), 45000);
+
+    let inventory = day1::parse(&input).unwrap();
+    assert_eq!(day1::part1(&inventory), 24000);
+    assert_eq!(day1::part2(&inventory), 45000);
+
+    assert_eq!(day1::stream_part1(input.as_bytes()).unwrap(), 24000);
+    assert_eq!(day1::stream_part2(input.as_bytes()).unwrap(), 45000);
+}
+
+#[test_case(""; "trimmed")]
+#[test_case("\n"; "trailing newline")]
+fn test_day2(ending: &str) {
+    let input = example(2, ending);
+
+    assert_eq!(day2::solve_part1(&day2::input_generator_part1(&input)), 15);
+    assert_eq!(day2::solve_part2(&day2::input_generator_part2(&input)), 12);
+
+    let guide = day2::parse(&input).unwrap();
+    assert_eq!(day2::part1(&guide), 15);
+    assert_eq!(day2::part2(&guide), 12);
+
+    assert_eq!(day2::stream_part1(input.as_bytes()).unwrap(), 15);
+    assert_eq!(day2::stream_part2(input.as_bytes()).unwrap(), 12);
+}
+
+#[test_case(""; "trimmed")]
+#[test_case("\n"; "trailing newline")]
+fn test_day3(ending: &str) {
+    let input = example(3, ending);
+
+    assert_eq!(day3::solve_part1(&day3::input_generator_part1(&input)), 157);
+    assert_eq!(day3::solve_part2(&day3::input_generator_part2(&input)), 70);
+
+    let supplies = day3::parse(&input).unwrap();
+    assert_eq!(day3::part1(&supplies), 157);
+    assert_eq!(day3::part2(&supplies), 70);
+
+    assert_eq!(day3::stream_part1(input.as_bytes()).unwrap(), 157);
+    assert_eq!(day3::stream_part2(input.as_bytes()).unwrap(), 70);
+}
+
+#[test_case(""; "trimmed")]
+#[test_case("\n"; "trailing newline")]
+fn test_day4(ending: &str) {
+    let input = example(4, ending);
+
+    let lines = day4::input_generator_part1(&input).unwrap();
+    assert_eq!(day4::solve_part1(&lines), 2);
+    assert_eq!(day4::solve_part2(&lines), 4);
+
+    let assignments = day4::parse(&input).unwrap();
+    assert_eq!(day4::part1(&assignments), 2);
+    assert_eq!(day4::part2(&assignments), 4);
+
+    assert_eq!(day4::stream_part1(input.as_bytes()).unwrap(), 2);
+    assert_eq!(day4::stream_part2(input.as_bytes()).unwrap(), 4);
+}
+
+#[test_case(""; "trimmed")]
+#[test_case("\n"; "trailing newline")]
+fn test_day5(ending: &str) {
+    let input = example(5, ending);
+
+    let procedure = day5::input_generator_part1(&input).unwrap();
+    assert_eq!(procedure.1.len(), 4);
+    assert_eq!(day5::solve_part1(&procedure).unwrap(), "CMZ");
+    assert_eq!(day5::solve_part2(&procedure).unwrap(), "MCD");
+
+    let procedure = day5::parse(&input).unwrap();
+    assert_eq!(procedure.instructions.len(), 4);
+    assert_eq!(day5::part1(&procedure).unwrap(), "CMZ");
+    assert_eq!(day5::part2(&procedure).unwrap(), "MCD");
+}
+
+#[test_case(""; "trimmed")]
+#[test_case("\n"; "trailing newline")]
+fn test_day6(ending: &str) {
+    let input = example(6, ending);
+
+    let chars = day6::input_generator_part1(&input);
+    assert_eq!(day6::solve_part1(&chars), 7);
+    assert_eq!(day6::solve_part2(&chars), 19);
+
+    let datastream = day6::parse(&input).unwrap();
+    assert_eq!(day6::part1(&datastream), Some(7));
+    assert_eq!(day6::part2(&datastream), Some(19));
+
+    assert_eq!(day6::stream_part1(input.as_bytes()).unwrap(), 7);
+    assert_eq!(day6::stream_part2(input.as_bytes()).unwrap(), 19);
+}
+
+#[test_case("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19)]
+#[test_case("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23)]
+#[test_case("nppdvjthqldpwncqszvftbrmjlhg", 6, 23)]
+#[test_case("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29)]
+#[test_case("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26)]
+fn test_day6_samples(signal: &str, packet: u64, message: u64) {
+    for input in [signal.to_string(), format!("{signal}\n")] {
+        let chars = day6::input_generator_part1(&input);
+        assert_eq!(day6::solve_part1(&chars), packet);
+        assert_eq!(day6::solve_part2(&chars), message);
+
+        let datastream = day6::parse(&input).unwrap();
+        assert_eq!(day6::part1(&datastream), Some(packet));
+        assert_eq!(day6::part2(&datastream), Some(message));
+
+        assert_eq!(day6::stream_part1(input.as_bytes()).unwrap(), packet);
+        assert_eq!(day6::stream_part2(input.as_bytes()).unwrap(), message);
+    }
+}
+
+#[test_case(""; "trimmed")]
+#[test_case("\n"; "trailing newline")]
+fn test_day7(ending: &str) {
+    let input = example(7, ending);
+
+    let log = day7::input_generator_part1(&input).unwrap();
+    assert_eq!(day7::solve_part1(&log).unwrap(), 95437);
+    assert_eq!(day7::solve_part2(&log).unwrap(), 24933642);
+
+    let fs = day7::parse(&input).unwrap();
+    assert_eq!(day7::part1(&fs), 95437);
+    assert_eq!(day7::part2(&fs), 24933642);
+}
+
+#[test_case(""; "trimmed")]
+#[test_case("\n"; "trailing newline")]
+fn test_day8(ending: &str) {
+    let input = example(8, ending);
+
+    let forest = day8::input_generator_part2(&input).unwrap();
+    assert_eq!(day8::solve_part1(&forest), 21);
+    assert_eq!(day8::solve_part2(&forest), 8);
+
+    let forest = day8::parse(&input).unwrap();
+    assert_eq!(day8::part1(&forest), 21);
+    assert_eq!(day8::part2(&forest), 8);
+}
+
+#[test_case(1, "24000", "45000")]
+#[test_case(2, "15", "12")]
+#[test_case(3, "157", "70")]
+#[test_case(4, "2", "4")]
+#[test_case(5, "CMZ", "MCD")]
+#[test_case(6, "7", "19")]
+#[test_case(7, "95437", "24933642")]
+#[test_case(8, "21", "8")]
+fn test_registry(day: u8, part1: &str, part2: &str) {
+    let registry = registry();
+    let solver = registry.get(2022, day).unwrap();
+
+    for ending in ["", "\n"] {
+        let answers = solver.solve(&example(day, ending)).unwrap();
+        assert_eq!(answers.part1, part1);
+        assert_eq!(answers.part2, part2);
+    }
+}